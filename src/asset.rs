@@ -0,0 +1,63 @@
+//! Assets (currencies, tokens, ...) held by clients.
+
+use std::{fmt, hash::Hash};
+
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// Name of an asset, e.g. `USD` or `BTC`.
+///
+/// Names are kept verbatim; the empty name is the default asset used by records without an
+/// `asset` column.
+#[derive(Deserialize, Serialize, Clone, Default, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Asset(Box<str>);
+
+impl From<&str> for Asset {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Table of values per key and asset, rows without an asset applying to the default one.
+#[derive(Debug)]
+pub(crate) struct AssetTable<K, V> {
+    rows: FxHashMap<(K, Option<Asset>), V>,
+}
+
+impl<K, V> Default for AssetTable<K, V> {
+    fn default() -> Self {
+        Self {
+            rows: FxHashMap::default(),
+        }
+    }
+}
+
+impl<K: Clone + Hash + Eq, V> AssetTable<K, V> {
+    /// Sets the value of a key in an asset, or in the default asset if `None`.
+    pub(crate) fn insert(&mut self, key: K, asset: Option<Asset>, value: V) {
+        self.rows.insert((key, asset), value);
+    }
+
+    /// Whether the table has no row.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the value of a key in an asset: the row of the asset, or else for the default
+    /// asset the row without one.
+    pub(crate) fn get(&self, key: &K, asset: &Asset, default_asset: &Asset) -> Option<&V> {
+        self.rows
+            .get(&(key.clone(), Some(asset.clone())))
+            .or_else(|| {
+                (asset == default_asset)
+                    .then(|| self.rows.get(&(key.clone(), None)))
+                    .flatten()
+            })
+    }
+}
@@ -1,20 +1,85 @@
 use clap::Parser;
 use csv::{ReaderBuilder, Trim, Writer};
-use toy_engine::{Config, Engine, Error};
+use toy_engine::{
+    clock::FixedClock, config::OutputMode, ClientTiers, Config, CreditLimits, Engine, Error,
+    FeeSchedule, Identifier, InterestRates, LimitTable, RateTable, Timestamp,
+};
 
 fn main() -> Result<(), Error> {
     // Parse the program config.
     let config = Config::parse();
 
     // Open the input file and process its content.
-    let mut engine = Engine::default();
+    let mut engine = Engine::default()
+        .with_id_kind(config.ids)
+        .with_default_asset(config.default_asset.as_str().into())
+        .with_lock_policy(config.lock_policy())
+        .with_dispute_policy(config.dispute_policy());
+    if let Some(now) = config.now {
+        engine = engine.with_clock(Box::new(FixedClock(Timestamp::from_secs(now))));
+    }
+    if let Some(secs) = config.dormant_after {
+        engine = engine.with_dormant_after(secs);
+    }
+    if let Some(path) = &config.credit_limits {
+        let reader = ReaderBuilder::new().trim(Trim::All).from_path(path)?;
+        engine = engine.with_credit_limits(CreditLimits::load_from_reader(reader, config.ids)?);
+    }
+    if let (Some(path), Some(house_account)) = (&config.fees, &config.house_account) {
+        let reader = ReaderBuilder::new().trim(Trim::All).from_path(path)?;
+        let house_account = Identifier::parse(house_account, config.ids)?;
+        engine = engine.with_fees(FeeSchedule::load_from_reader(reader)?, house_account.into());
+    }
+    if let Some(path) = &config.interest_rates {
+        let reader = ReaderBuilder::new().trim(Trim::All).from_path(path)?;
+        engine = engine.with_interest_rates(InterestRates::load_from_reader(reader)?);
+    }
+    if let Some(path) = &config.client_tiers {
+        let reader = ReaderBuilder::new().trim(Trim::All).from_path(path)?;
+        engine = engine.with_client_tiers(ClientTiers::load_from_reader(reader, config.ids)?);
+    }
+    if let Some(path) = &config.limits {
+        let reader = ReaderBuilder::new().trim(Trim::All).from_path(path)?;
+        engine = engine.with_limits(LimitTable::load_from_reader(reader, config.ids)?);
+    }
+    if let Some(check) = config.check_invariants {
+        engine = engine.with_invariant_check(check);
+    }
     let reader = ReaderBuilder::new()
         .trim(Trim::All)
-        .from_path(config.input_file)?;
-    engine.load_from_reader(reader)?;
-    // Dump the accounts to stdout.
+        .from_path(&config.input_file)?;
+    if config.admin {
+        engine.load_admin_from_reader(reader)?;
+    } else {
+        engine.load_from_reader(reader)?;
+    }
+    // Dump the requested report to stdout.
     let writer = Writer::from_writer(std::io::stdout());
-    engine.dump_accounts(writer)?;
+    match config.output {
+        OutputMode::Accounts => engine.dump_accounts(writer)?,
+        OutputMode::Assets => engine.dump_asset_accounts(writer)?,
+        OutputMode::Transactions => engine.dump_transactions(writer)?,
+        OutputMode::AdminLog => engine.dump_admin_log(writer)?,
+        OutputMode::Suspense => engine.dump_suspense(writer)?,
+        OutputMode::DisputeHistory => engine.dump_dispute_history(writer)?,
+        OutputMode::DisputeAging => engine.dump_dispute_aging(writer)?,
+        OutputMode::Holds => engine.dump_holds(writer)?,
+        OutputMode::Escrows => engine.dump_escrows(writer)?,
+        OutputMode::Refusals => engine.dump_refusals(writer)?,
+        OutputMode::Interest => engine.dump_interest(writer)?,
+        OutputMode::Consolidated => {
+            let path = config.fx_rates.expect("required by the CLI");
+            let base = config.base_asset.expect("required by the CLI");
+            let rates =
+                RateTable::load_from_reader(ReaderBuilder::new().trim(Trim::All).from_path(path)?)?;
+            engine.dump_consolidated(
+                writer.into_inner().map_err(|e| e.into_error())?,
+                &rates,
+                &base.as_str().into(),
+                config.fx_at.map(Timestamp::from_secs),
+            )?
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,109 @@
+//! Time of transactions and the engine clock.
+
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Point in time, in seconds since the Unix epoch.
+#[derive(
+    Deserialize, Serialize, Clone, Copy, Debug, Default, Hash, Eq, PartialEq, Ord, PartialOrd,
+)]
+#[serde(transparent)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    /// Creates a timestamp from seconds since the Unix epoch.
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs)
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+
+    /// Whole days since the Unix epoch.
+    pub fn as_days(&self) -> u64 {
+        self.0 / (24 * 60 * 60)
+    }
+
+    /// The start of the UTC day.
+    pub fn day_start(&self) -> Self {
+        Self(self.as_days() * (24 * 60 * 60))
+    }
+
+    /// The UTC calendar date.
+    pub fn date(&self) -> Date {
+        // Days since 0000-03-01, years starting in March so that leap days end them.
+        let days = self.as_days() + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        } as u32;
+        let year = era * 400 + year_of_era + u64::from(month <= 2);
+        Date { year, month, day }
+    }
+}
+
+/// A UTC calendar date.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Date {
+    /// The year.
+    pub year: u64,
+    /// The month, from 1 to 12.
+    pub month: u32,
+    /// The day of the month, from 1 to 31.
+    pub day: u32,
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Source of time for the records without a `timestamp` column.
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Timestamp;
+}
+
+/// Clock reading the system time.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Timestamp(elapsed.as_secs())
+    }
+}
+
+/// Clock always returning the same time, for reproducible runs.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock(pub Timestamp);
+
+impl Clock for FixedClock {
+    fn now(&self) -> Timestamp {
+        self.0
+    }
+}
+
+impl Default for Box<dyn Clock> {
+    fn default() -> Self {
+        Box::new(SystemClock)
+    }
+}
@@ -1,6 +1,8 @@
 //! Everything about program configuration.
 
-use clap::{arg, command, Parser};
+use clap::{Parser, ValueEnum};
+
+use crate::{policy::ExpiryAction, DisputePolicy, IdKind, InvariantCheck, LockPolicy};
 
 /// Program CLI configuration.
 #[derive(Parser, Debug)]
@@ -9,4 +11,144 @@ pub struct Config {
     /// Path to the input file.
     #[arg()]
     pub input_file: String,
+    /// Treat the input as a trusted operator stream, accepting administrative operations.
+    #[arg(long)]
+    pub admin: bool,
+    /// When to verify the bookkeeping invariants (defaults to end-of-run in debug builds, off
+    /// otherwise).
+    #[arg(long, value_enum)]
+    pub check_invariants: Option<InvariantCheck>,
+    /// Kind of the client and transaction ids: `u64` numbers, or strings kept verbatim.
+    #[arg(long, value_enum, default_value_t = IdKind::Numeric)]
+    pub ids: IdKind,
+    /// Asset of the records without an `asset` column.
+    #[arg(long, default_value = "")]
+    pub default_asset: String,
+    /// Path to the exchange rate table (`pair,rate,effective` columns).
+    #[arg(long, required_if_eq("output", "consolidated"))]
+    pub fx_rates: Option<String>,
+    /// Asset the consolidated output is converted into.
+    #[arg(long, required_if_eq("output", "consolidated"))]
+    pub base_asset: Option<String>,
+    /// Point in time (seconds since the Unix epoch) of the rates to use, the latest by default.
+    #[arg(long)]
+    pub fx_at: Option<u64>,
+    /// Time (seconds since the Unix epoch) of the records without a `timestamp` column, the
+    /// system time by default.
+    #[arg(long)]
+    pub now: Option<u64>,
+    /// Number of chargebacks locking an account, 0 to never lock on chargebacks.
+    #[arg(long, default_value_t = 1)]
+    pub lock_after_chargebacks: u32,
+    /// Operations refused by locked accounts.
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [LockBlock::Deposits, LockBlock::Withdrawals])]
+    pub lock_blocks: Vec<LockBlock>,
+    /// Keep the pending disputes of locked accounts open instead of resolving them.
+    #[arg(long)]
+    pub lock_freezes_disputes: bool,
+    /// Unlock accounts locked by chargebacks once reversals bring them back under the threshold.
+    #[arg(long)]
+    pub reversal_unlocks: bool,
+    /// Number of dispute cycles after which a transaction can't be disputed, 0 for no limit.
+    #[arg(long, default_value_t = 0)]
+    pub max_dispute_cycles: u32,
+    /// Seconds after which open disputes expire.
+    #[arg(long)]
+    pub dispute_expires_after: Option<u64>,
+    /// Number of subsequent transactions after which open disputes expire.
+    #[arg(long)]
+    pub dispute_expires_after_transactions: Option<u64>,
+    /// What happens to expired disputes.
+    #[arg(long, value_enum, default_value_t = ExpiryAction::Resolve)]
+    pub dispute_expiry_action: ExpiryAction,
+    /// Path to the overdraft limits of the clients with a credit line (`client,limit,asset`
+    /// columns).
+    #[arg(long)]
+    pub credit_limits: Option<String>,
+    /// Path to the fee schedule (`operation,asset,flat,percent,min,max` columns).
+    #[arg(long, requires = "house_account")]
+    pub fees: Option<String>,
+    /// Client id of the house account, credited with the fees.
+    #[arg(long)]
+    pub house_account: Option<String>,
+    /// Path to the yearly interest rates per tier (`tier,asset,rate,convention` columns).
+    #[arg(long)]
+    pub interest_rates: Option<String>,
+    /// Path to the tiers of the clients (`client,tier` columns).
+    #[arg(long)]
+    pub client_tiers: Option<String>,
+    /// Path to the transaction limits per client or tier (`client,tier,asset,max_amount,
+    /// daily_withdrawals,monthly_withdrawals,daily_transactions,monthly_transactions` columns).
+    #[arg(long)]
+    pub limits: Option<String>,
+    /// Seconds without transactions after which active accounts go dormant.
+    #[arg(long)]
+    pub dormant_after: Option<u64>,
+    /// What to write to stdout.
+    #[arg(long, value_enum, default_value_t = OutputMode::Accounts)]
+    pub output: OutputMode,
+}
+
+impl Config {
+    /// Builds the lock policy from the CLI options.
+    pub fn lock_policy(&self) -> LockPolicy {
+        LockPolicy {
+            blocks_deposits: self.lock_blocks.contains(&LockBlock::Deposits),
+            blocks_withdrawals: self.lock_blocks.contains(&LockBlock::Withdrawals),
+            blocks_disputes: self.lock_blocks.contains(&LockBlock::Disputes),
+            chargebacks_to_lock: self.lock_after_chargebacks,
+            resolves_pending_disputes: !self.lock_freezes_disputes,
+            reversal_unlocks: self.reversal_unlocks,
+        }
+    }
+
+    /// Builds the dispute policy from the CLI options.
+    pub fn dispute_policy(&self) -> DisputePolicy {
+        DisputePolicy {
+            max_cycles: self.max_dispute_cycles,
+            expire_after: self.dispute_expires_after,
+            expire_after_transactions: self.dispute_expires_after_transactions,
+            on_expiry: self.dispute_expiry_action,
+        }
+    }
+}
+
+/// Operation a lock can block.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LockBlock {
+    /// Deposits.
+    Deposits,
+    /// Withdrawals.
+    Withdrawals,
+    /// New disputes.
+    Disputes,
+}
+
+/// Kind of report written by the program.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputMode {
+    /// One row per client, with the balances of the default asset.
+    Accounts,
+    /// One row per client and asset.
+    Assets,
+    /// One row per client, converted into the base asset.
+    Consolidated,
+    /// One row per disputable transaction, with its state and timestamp.
+    Transactions,
+    /// One row per administrative operation.
+    AdminLog,
+    /// One row per deposit booked into the suspense account.
+    Suspense,
+    /// One row per dispute operation applied to a transaction.
+    DisputeHistory,
+    /// Funds held by open disputes, per asset and dispute age.
+    DisputeAging,
+    /// Held funds per client, asset and hold reason.
+    Holds,
+    /// One row per open escrow.
+    Escrows,
+    /// One row per transaction refused for breaching a limit.
+    Refusals,
+    /// One row per interest posting.
+    Interest,
 }
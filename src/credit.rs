@@ -0,0 +1,52 @@
+//! Overdraft limits of the clients with an approved credit line.
+
+use csv::Reader;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{asset::AssetTable, engine::ClientId, Asset, Error, IdKind};
+
+/// One row of the credit limit table.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct CreditLimit {
+    /// The client granted the credit line.
+    pub client: ClientId,
+    /// How far below zero the client's available funds may go.
+    pub limit: Decimal,
+    /// The asset of the credit line, the default asset if `None`.
+    pub asset: Option<Asset>,
+}
+
+/// Table of the overdraft limits per client and asset, clients without one having no credit.
+#[derive(Default, Debug)]
+pub struct CreditLimits {
+    limits: AssetTable<ClientId, Decimal>,
+}
+
+impl CreditLimits {
+    /// Loads a credit limit table from a `csv::Reader` with the `client,limit,asset` columns,
+    /// reading the client ids as the given kind.
+    pub fn load_from_reader<R: std::io::Read>(
+        mut reader: Reader<R>,
+        kind: IdKind,
+    ) -> Result<Self, Error> {
+        let mut limits = AssetTable::default();
+        for result in reader.deserialize() {
+            let limit: CreditLimit = result?;
+            let client = limit.client.into_kind(kind)?;
+            if limit.limit < Decimal::ZERO {
+                return Err(Error::InvalidCreditLimit(client));
+            }
+            limits.insert(client, limit.asset, limit.limit);
+        }
+        Ok(Self { limits })
+    }
+
+    /// Returns the overdraft limit of a client in an asset, zero without a credit line.
+    pub fn limit(&self, client: &ClientId, asset: &Asset, default_asset: &Asset) -> Decimal {
+        self.limits
+            .get(client, asset, default_asset)
+            .copied()
+            .unwrap_or_default()
+    }
+}
@@ -1,6 +1,10 @@
 //! Module for transaction processing.
 
-use std::ops::Not;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    ops::{AddAssign, Not},
+};
 
 use csv::{Reader, Writer};
 use rust_decimal::Decimal;
@@ -8,11 +12,23 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    clock::Clock,
+    credit::CreditLimits,
+    fee::FeeSchedule,
+    fx::{self, Rate, RateTable},
+    hold::{Hold, HoldReason},
+    interest::{round_interest, InterestRates},
+    invariant::{self, InvariantCheck, Totals},
+    lifecycle::{AccountState, StateEvent, StateReason},
+    limits::{LimitBreach, LimitTable, Usage},
+    policy::{DisputePolicy, ExpiryAction, LockPolicy},
     transaction::{
-        Chargeback, Deposit, DisputableTransaction, Dispute, DisputeState, Operation, Resolve,
-        TransactionId, TransactionRecord, Withdrawal,
+        Adjustment, Authorization, Capture, Chargeback, ChargebackReversal, CompletedWithdrawal,
+        Deposit, DisputableTransaction, Dispute, DisputeAge, DisputeEvent, DisputeState, Escrow,
+        EscrowCancel, EscrowOpening, EscrowRelease, Operation, PendingAuthorization, Refund,
+        Resolve, SuspendedDeposit, TransactionId, TransactionRecord, Transfer, Void, Withdrawal,
     },
-    Error,
+    Asset, ClientTiers, Error, IdKind, Identifier, Timestamp, Wallet,
 };
 
 /// Transaction engine responsible to store and process transactions.
@@ -20,49 +36,627 @@ use crate::{
 pub struct Engine {
     disputable_transactions: FxHashMap<TransactionId, DisputableTransaction>,
     clients: FxHashMap<ClientId, ClientData>,
+    totals: BTreeMap<Asset, Totals>,
+    invariant_check: InvariantCheck,
+    default_asset: Asset,
+    clock: Box<dyn Clock>,
+    admin_log: Vec<AdminEntry>,
+    lock_policy: LockPolicy,
+    dormant_after: Option<u64>,
+    suspense: FxHashMap<TransactionId, SuspendedDeposit>,
+    dispute_policy: DisputePolicy,
+    processed: u64,
+    latest_timestamp: Option<Timestamp>,
+    open_disputes: BTreeSet<TransactionId>,
+    authorizations: FxHashMap<TransactionId, PendingAuthorization>,
+    withdrawals: FxHashMap<TransactionId, CompletedWithdrawal>,
+    escrows: FxHashMap<TransactionId, Escrow>,
+    /// Closed duplicate accounts, with the client they were merged into.
+    merged: FxHashMap<ClientId, ClientId>,
+    credit_limits: CreditLimits,
+    tiers: ClientTiers,
+    limits: LimitTable,
+    refusals: Vec<Refusal>,
+    fees: FeeSchedule,
+    house_account: Option<ClientId>,
+    interest_rates: InterestRates,
+    interest_counted_until: Option<Timestamp>,
+    interest: Vec<InterestPosting>,
+    id_kind: IdKind,
 }
 
 impl Engine {
+    /// Sets when the engine verifies its bookkeeping invariants.
+    pub fn with_invariant_check(mut self, invariant_check: InvariantCheck) -> Self {
+        self.invariant_check = invariant_check;
+        self
+    }
+
+    /// Sets how the client and transaction ids are read.
+    pub fn with_id_kind(mut self, id_kind: IdKind) -> Self {
+        self.id_kind = id_kind;
+        self
+    }
+
+    /// Sets the asset of records without an `asset` column.
+    pub fn with_default_asset(mut self, default_asset: Asset) -> Self {
+        self.default_asset = default_asset;
+        self
+    }
+
+    /// Sets the clock giving the time of the records without a `timestamp` column.
+    pub fn with_clock(mut self, clock: Box<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets what locking an account blocks, and when accounts get locked.
+    pub fn with_lock_policy(mut self, lock_policy: LockPolicy) -> Self {
+        self.lock_policy = lock_policy;
+        self
+    }
+
+    /// Sets how many times transactions can be disputed, and how long disputes can stay open.
+    pub fn with_dispute_policy(mut self, dispute_policy: DisputePolicy) -> Self {
+        self.dispute_policy = dispute_policy;
+        self
+    }
+
+    /// Sets the overdraft limits of the clients with a credit line.
+    pub fn with_credit_limits(mut self, credit_limits: CreditLimits) -> Self {
+        self.credit_limits = credit_limits;
+        self
+    }
+
+    /// Sets the tiers of the clients.
+    pub fn with_client_tiers(mut self, tiers: ClientTiers) -> Self {
+        self.tiers = tiers;
+        self
+    }
+
+    /// Sets the transaction limits of the clients and tiers.
+    pub fn with_limits(mut self, limits: LimitTable) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the fees charged on operations, and the house account they are credited to.
+    pub fn with_fees(mut self, fees: FeeSchedule, house_account: ClientId) -> Self {
+        self.fees = fees;
+        self.house_account = Some(house_account);
+        self
+    }
+
+    /// Sets the interest rates earned by the available balances.
+    pub fn with_interest_rates(mut self, interest_rates: InterestRates) -> Self {
+        self.interest_rates = interest_rates;
+        self
+    }
+
+    /// Sets the number of seconds without transactions after which active accounts go dormant.
+    pub fn with_dormant_after(mut self, secs: u64) -> Self {
+        self.dormant_after = Some(secs);
+        self
+    }
+
     /// Loads transactions from a `csv::Reader`.
-    pub fn load_from_reader<R: std::io::Read>(
+    ///
+    /// Administrative operations are ignored, see [`Engine::load_admin_from_reader`].
+    pub fn load_from_reader<R: std::io::Read>(&mut self, reader: Reader<R>) -> Result<(), Error> {
+        self.load(reader, false)
+    }
+
+    /// Loads transactions from a trusted operator `csv::Reader`, which may also contain
+    /// administrative operations.
+    pub fn load_admin_from_reader<R: std::io::Read>(
         &mut self,
-        mut reader: Reader<R>,
+        reader: Reader<R>,
     ) -> Result<(), Error> {
+        self.load(reader, true)
+    }
+
+    fn load<R: std::io::Read>(&mut self, mut reader: Reader<R>, admin: bool) -> Result<(), Error> {
         let iter = reader.deserialize();
 
         for result in iter {
             let record: TransactionRecord = result?;
-            self.load_record(&record);
+            let record = record.into_id_kind(self.id_kind)?;
+            let tx = record.tx.clone();
+            if record.r#type.is_admin() && !admin {
+                continue;
+            }
+            self.load_record(record);
+            if self.invariant_check == InvariantCheck::EachTransaction {
+                invariant::check(self).map_err(|violation| Error::InvariantViolation {
+                    tx: Some(tx),
+                    violation,
+                })?;
+            }
+        }
+        // End-of-run expiries and dormancy are as of the latest record, not of when the program
+        // runs.
+        if let Some(latest) = self.latest_timestamp {
+            self.expire_disputes(latest, None);
+            self.apply_dormancy(latest);
+        }
+        if self.invariant_check == InvariantCheck::EndOfRun {
+            invariant::check(self).map_err(|violation| Error::InvariantViolation {
+                tx: None,
+                violation,
+            })?;
         }
         Ok(())
     }
 
     /// Loads one record (= one transaction) in the engine.
-    fn load_record(&mut self, record: &TransactionRecord) {
+    fn load_record(&mut self, mut record: TransactionRecord) {
+        // Disputes naming a merged account apply to the account it was merged into.
+        if matches!(
+            record.r#type,
+            Operation::Dispute
+                | Operation::Resolve
+                | Operation::Chargeback
+                | Operation::ChargebackReversal
+        ) {
+            record.client = self.merge_target(record.client);
+        }
+        // Time must not go backwards for a client.
+        let timestamp = record.timestamp.unwrap_or_else(|| self.clock.now());
+        self.latest_timestamp = self.latest_timestamp.max(Some(timestamp));
+        // Balances earn their interest up to the day of the record, before it changes them.
+        self.count_interest(timestamp);
+        let client_id = record.client.clone();
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            if client.last_timestamp.is_some_and(|last| timestamp < last) {
+                return;
+            }
+            // The client may have gone dormant since its previous transaction, unlike the house
+            // account.
+            if self.house_account.as_ref() != Some(&client_id) {
+                client.apply_dormancy(self.dormant_after, timestamp, Some(&record.tx));
+            }
+        }
+        // Disputes open for too long expire before the record is processed.
+        self.expire_disputes(timestamp, Some(&record.tx));
+
+        if record.r#type.is_admin() {
+            self.load_admin_record(record, timestamp);
+        } else {
+            self.load_client_record(record, timestamp);
+        }
+
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.last_timestamp = Some(timestamp);
+        }
+        self.processed += 1;
+    }
+
+    /// Loads one administrative record, logging it if applied.
+    fn load_admin_record(&mut self, record: TransactionRecord, timestamp: Timestamp) {
+        // Administrative operations must say who performed them and why.
+        let (Some(operator), Some(reason)) = (record.operator, record.reason) else {
+            return;
+        };
+        let event = |state| StateEvent {
+            state,
+            reason: StateReason::Operator(reason.clone()),
+            tx: Some(record.tx.clone()),
+            timestamp,
+        };
+        // Closing pays out every wallet, each payout being logged.
+        let mut payouts = Vec::new();
+        let applied = match record.r#type {
+            Operation::Lock => self.process_transition(&record.client, event(AccountState::Frozen)),
+            Operation::Unlock => {
+                self.process_transition(&record.client, event(AccountState::Active))
+            }
+            Operation::Restrict => {
+                self.process_transition(&record.client, event(AccountState::Restricted))
+            }
+            Operation::Close => {
+                let event = event(AccountState::Closed);
+                self.process_close(&record.client, &record.tx, event, &mut payouts)
+            }
+            Operation::Release => self.process_release(&record.client, &record.tx),
+            Operation::Reversal => self.process_reversal(&record.client, &record.tx, timestamp),
+            Operation::Accrue => self.process_accrue(&record.tx, timestamp),
+            Operation::Merge => self.process_merge(
+                &record.client,
+                record
+                    .to
+                    .as_ref()
+                    .expect("merges must indicate the target client"),
+                event(AccountState::Closed),
+            ),
+            Operation::Adjust => self.process_adjust(Adjustment::new(
+                record.client.clone(),
+                record.wallet.clone().unwrap_or_default(),
+                record
+                    .asset
+                    .clone()
+                    .unwrap_or_else(|| self.default_asset.clone()),
+                record.amount.expect("adjustments must indicate the amount"),
+            )),
+            _ => unreachable!("not an administrative operation"),
+        };
+        if applied.not() {
+            return;
+        }
+        let entry = AdminEntry {
+            tx: record.tx,
+            client: record.client,
+            r#type: record.r#type,
+            wallet: record.wallet,
+            asset: record.asset,
+            amount: record.amount,
+            operator,
+            reason,
+            timestamp,
+        };
+        if payouts.is_empty() {
+            self.admin_log.push(entry);
+        } else {
+            self.admin_log.extend(
+                payouts
+                    .into_iter()
+                    .map(|(wallet, asset, amount)| AdminEntry {
+                        wallet: Some(wallet),
+                        asset: Some(asset),
+                        amount: Some(amount),
+                        ..entry.clone()
+                    }),
+            );
+        }
+    }
+
+    /// Loads one record submitted on behalf of a client.
+    fn load_client_record(&mut self, record: TransactionRecord, timestamp: Timestamp) {
+        // The house account only collects fees: no client record may move its funds.
+        if let Some(house) = &self.house_account {
+            if record.client == *house || record.to.as_ref() == Some(house) {
+                return;
+            }
+        }
+        // Funds movements must be within the client's limits.
+        if record.r#type.moves_funds() && self.within_limits(&record, timestamp).not() {
+            return;
+        }
         match record.r#type {
             Operation::Deposit => self.process_deposit(
                 record.tx,
                 Deposit::new(
                     record.client,
+                    record.wallet.unwrap_or_default(),
+                    record.asset.unwrap_or_else(|| self.default_asset.clone()),
                     record.amount.expect("deposits must indicate the amount"),
                 ),
+                timestamp,
             ),
-            Operation::Withdrawal => self.process_withdrawal(Withdrawal::new(
+            Operation::Withdrawal => self.process_withdrawal(
+                record.tx,
+                Withdrawal::new(
+                    record.client,
+                    record.wallet.unwrap_or_default(),
+                    record.asset.unwrap_or_else(|| self.default_asset.clone()),
+                    record.amount.expect("withdrawals must indicate the amount"),
+                ),
+                timestamp,
+            ),
+            Operation::Transfer => self.process_transfer(
+                record.tx,
+                Transfer::new(
+                    record.client,
+                    record.wallet.unwrap_or_default(),
+                    record.to.expect("transfers must indicate the recipient"),
+                    record.to_wallet.unwrap_or_default(),
+                    record.asset.unwrap_or_else(|| self.default_asset.clone()),
+                    record.amount.expect("transfers must indicate the amount"),
+                ),
+                timestamp,
+            ),
+            Operation::Refund => self.process_refund(
+                Refund::new(record.client, record.tx, record.asset, record.amount),
+                timestamp,
+            ),
+            Operation::Authorize => self.process_authorize(
+                record.tx,
+                Authorization::new(
+                    record.client,
+                    record.wallet.unwrap_or_default(),
+                    record.asset.unwrap_or_else(|| self.default_asset.clone()),
+                    record
+                        .amount
+                        .expect("authorizations must indicate the amount"),
+                ),
+                timestamp,
+            ),
+            Operation::Capture => self.process_capture(Capture::new(
+                record.client,
+                record.tx,
+                record.asset,
+                record.amount,
+            )),
+            Operation::Void => self.process_void(Void::new(
                 record.client,
-                record.amount.expect("withdrawals must indicate the amount"),
+                record.tx,
+                record.asset,
+                record.amount,
             )),
-            Operation::Dispute => self.process_dispute(Dispute::new(record.client, record.tx)),
-            Operation::Resolve => self.process_resolve(Resolve::new(record.client, record.tx)),
-            Operation::Chargeback => {
-                self.process_chargeback(Chargeback::new(record.client, record.tx))
+            Operation::EscrowOpen => self.process_escrow_open(
+                record.tx,
+                EscrowOpening::new(
+                    record.client,
+                    record.wallet.unwrap_or_default(),
+                    record.to.expect("escrows must indicate the payee"),
+                    record.to_wallet.unwrap_or_default(),
+                    record.asset.unwrap_or_else(|| self.default_asset.clone()),
+                    record.amount.expect("escrows must indicate the amount"),
+                ),
+                timestamp,
+            ),
+            Operation::EscrowRelease => {
+                self.process_escrow_release(EscrowRelease::new(record.client, record.tx))
+            }
+            Operation::EscrowCancel => {
+                self.process_escrow_cancel(EscrowCancel::new(record.client, record.tx))
             }
+            Operation::Dispute => self.process_dispute(
+                Dispute::new(record.client, record.tx, record.asset, record.amount),
+                timestamp,
+            ),
+            Operation::Resolve => self.process_resolve(
+                Resolve::new(
+                    record.client,
+                    record.tx.clone(),
+                    record.asset,
+                    record.amount,
+                ),
+                timestamp,
+                Some(record.tx),
+            ),
+            Operation::Chargeback => self.process_chargeback(
+                Chargeback::new(
+                    record.client,
+                    record.tx.clone(),
+                    record.asset,
+                    record.amount,
+                ),
+                timestamp,
+                Some(record.tx),
+            ),
+            Operation::ChargebackReversal => self.process_chargeback_reversal(
+                ChargebackReversal::new(record.client, record.tx, record.asset, record.amount),
+                timestamp,
+            ),
+            Operation::Lock
+            | Operation::Unlock
+            | Operation::Adjust
+            | Operation::Restrict
+            | Operation::Close
+            | Operation::Release
+            | Operation::Reversal
+            | Operation::Merge
+            | Operation::Accrue => unreachable!("administrative operation"),
         };
     }
 
-    /// Writes the accounts state into a `csv::Writer`.
+    /// Writes the accounts state into a `csv::Writer`, reporting the default asset only.
+    ///
+    /// Each client gets an aggregate row over all its wallets, followed by one row per wallet
+    /// if it uses other wallets than the `main` one.
     pub fn dump_accounts<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
-        for (id, data) in self.clients {
-            writer.serialize(ClientRecord::from_id_and_data(id, data))?;
+        for (id, data) in &self.clients {
+            let limit = self.credit_limit(id);
+            writer.serialize(ClientRecord::from_id_and_data(
+                id.clone(),
+                data,
+                &self.default_asset,
+                limit,
+            ))?;
+            if data.has_wallets() {
+                for wallet in data.wallets.keys() {
+                    writer.serialize(ClientRecord::from_wallet(
+                        id.clone(),
+                        data,
+                        wallet,
+                        &self.default_asset,
+                        limit,
+                    ))?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the disputable transactions into a `csv::Writer`.
+    pub fn dump_transactions<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for record in self.transactions_ordered() {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the dispute history of every disputable transaction into a `csv::Writer`.
+    pub fn dump_dispute_history<W: std::io::Write>(
+        self,
+        mut writer: Writer<W>,
+    ) -> Result<(), Error> {
+        for event in self.dispute_history_ordered() {
+            writer.serialize(event)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the dispute operations applied to a transaction, in order.
+    pub fn dispute_history(&self, tx: &TransactionId) -> Option<&[DisputeEvent]> {
+        self.disputable_transactions
+            .get(tx)
+            .map(|disputable_tx| disputable_tx.history.as_slice())
+    }
+
+    /// Returns the dispute operations of all transactions, ordered by the timestamp and id of
+    /// the disputed transaction, then in the order they were applied.
+    pub fn dispute_history_ordered(&self) -> Vec<DisputeEvent> {
+        let mut vec: Vec<_> = self.disputable_transactions.iter().collect();
+        vec.sort_by(|(a_tx, a), (b_tx, b)| (a.timestamp, a_tx).cmp(&(b.timestamp, b_tx)));
+        vec.into_iter()
+            .flat_map(|(_, disputable_tx)| disputable_tx.history.iter().cloned())
+            .collect()
+    }
+
+    /// Writes the funds held by open disputes, per asset and dispute age, into a `csv::Writer`.
+    pub fn dump_dispute_aging<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        let now = self.latest_timestamp.unwrap_or_else(|| self.clock.now());
+        for record in self.dispute_aging(now) {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the funds held by open disputes at `now`, per asset and dispute age, ordered by
+    /// asset and age.
+    pub fn dispute_aging(&self, now: Timestamp) -> Vec<AgingRecord> {
+        let mut groups: BTreeMap<(Asset, DisputeAge), AgingRecord> = BTreeMap::new();
+        for tx in &self.open_disputes {
+            let disputable_tx = &self.disputable_transactions[tx];
+            let asset = &disputable_tx.deposit.asset;
+            let age = DisputeAge::from_secs(
+                now.as_secs()
+                    .saturating_sub(disputable_tx.opened_at.as_secs()),
+            );
+            let record = groups
+                .entry((asset.clone(), age))
+                .or_insert_with(|| AgingRecord::new(asset.clone(), age, 0, Decimal::ZERO));
+            record.disputes += 1;
+            record.held += disputable_tx.disputed;
+        }
+        groups.into_values().collect()
+    }
+
+    /// Writes the held funds of every client, per asset and hold reason, into a `csv::Writer`.
+    pub fn dump_holds<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for record in self.holds_ordered() {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the held funds of every client per asset and hold reason, ordered by client id,
+    /// asset and reason.
+    pub fn holds_ordered(&self) -> Vec<HoldRecord> {
+        let mut groups: BTreeMap<(&ClientId, &Asset, HoldReason), HoldRecord> = BTreeMap::new();
+        for (id, data) in &self.clients {
+            for hold in data.holds() {
+                let record = groups
+                    .entry((id, &hold.asset, hold.reason))
+                    .or_insert_with(|| {
+                        HoldRecord::new(
+                            id.clone(),
+                            hold.asset.clone(),
+                            hold.reason,
+                            0,
+                            Decimal::ZERO,
+                        )
+                    });
+                record.holds += 1;
+                record.held += hold.amount;
+            }
+        }
+        groups.into_values().collect()
+    }
+
+    /// Writes the interest postings into a `csv::Writer`.
+    pub fn dump_interest<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for posting in &self.interest {
+            writer.serialize(posting)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the interest postings, in run order then ordered by client, wallet and asset.
+    pub fn interest_postings(&self) -> &[InterestPosting] {
+        &self.interest
+    }
+
+    /// Writes the transactions refused for breaching limits into a `csv::Writer`.
+    pub fn dump_refusals<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for refusal in &self.refusals {
+            writer.serialize(refusal)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the transactions refused for breaching limits, in processing order.
+    pub fn refusals(&self) -> &[Refusal] {
+        &self.refusals
+    }
+
+    /// Writes the open escrows into a `csv::Writer`.
+    pub fn dump_escrows<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for record in self.escrows_ordered() {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the open escrows, ordered by timestamp and transaction id.
+    pub fn escrows_ordered(&self) -> Vec<EscrowRecord> {
+        let mut vec: Vec<_> = self
+            .escrows
+            .iter()
+            .filter(|(_, escrow)| escrow.open)
+            .map(|(tx, escrow)| {
+                let opening = &escrow.opening;
+                EscrowRecord {
+                    tx: tx.clone(),
+                    payer: opening.payer.clone(),
+                    payee: opening.payee.clone(),
+                    asset: opening.asset.clone(),
+                    amount: opening.amount,
+                    timestamp: escrow.timestamp,
+                }
+            })
+            .collect();
+        vec.sort_by(|a, b| (a.timestamp, &a.tx).cmp(&(b.timestamp, &b.tx)));
+        vec
+    }
+
+    /// Writes the log of administrative operations into a `csv::Writer`.
+    pub fn dump_admin_log<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for entry in self.admin_log {
+            writer.serialize(entry)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the administrative operations applied so far, in order.
+    pub fn admin_log(&self) -> &[AdminEntry] {
+        &self.admin_log
+    }
+
+    /// Writes one row per client and asset into a `csv::Writer`.
+    pub fn dump_asset_accounts<W: std::io::Write>(
+        self,
+        mut writer: Writer<W>,
+    ) -> Result<(), Error> {
+        for (id, data) in &self.clients {
+            for (asset, balance) in data.balances() {
+                writer.serialize(AssetRecord::from_balance(
+                    id.clone(),
+                    asset.clone(),
+                    balance,
+                    data.state,
+                ))?;
+            }
         }
         writer.flush()?;
         Ok(())
@@ -78,145 +672,2143 @@ impl Engine {
         let mut vec: Vec<_> = self
             .clients
             .iter()
-            .map(|(id, data)| ClientRecord::from_id_and_data(*id, *data))
+            .map(|(id, data)| {
+                let limit = self.credit_limit(id);
+                ClientRecord::from_id_and_data(id.clone(), data, &self.default_asset, limit)
+            })
             .collect();
         vec.sort_by(|a, b| a.client.cmp(&b.client));
         vec
     }
 
+    /// Returns one record per client and wallet, ordered by client id and wallet.
+    pub fn wallets_ordered(&self) -> Vec<ClientRecord> {
+        let mut vec: Vec<_> = self
+            .clients
+            .iter()
+            .flat_map(|(id, data)| {
+                let limit = self.credit_limit(id);
+                data.wallets.keys().map(move |wallet| {
+                    ClientRecord::from_wallet(id.clone(), data, wallet, &self.default_asset, limit)
+                })
+            })
+            .collect();
+        vec.sort_by(|a, b| (&a.client, &a.wallet).cmp(&(&b.client, &b.wallet)));
+        vec
+    }
+
+    /// Returns the disputable transactions ordered by timestamp and transaction id.
+    pub fn transactions_ordered(&self) -> Vec<TransactionReport> {
+        let mut vec: Vec<_> = self
+            .disputable_transactions
+            .iter()
+            .map(|(tx, disputable_tx)| {
+                TransactionReport::from_disputable(tx.clone(), disputable_tx)
+            })
+            .collect();
+        vec.sort_by(|a, b| (a.timestamp, &a.tx).cmp(&(b.timestamp, &b.tx)));
+        vec
+    }
+
+    /// Returns one record per client and asset, ordered by client id and asset.
+    pub fn assets_ordered(&self) -> Vec<AssetRecord> {
+        let mut vec: Vec<_> = self
+            .clients
+            .iter()
+            .flat_map(|(id, data)| {
+                data.balances().map(|(asset, balance)| {
+                    AssetRecord::from_balance(id.clone(), asset.clone(), balance, data.state)
+                })
+            })
+            .collect();
+        vec.sort_by(|a, b| (&a.client, &a.asset).cmp(&(&b.client, &b.asset)));
+        vec
+    }
+
+    /// Writes each client's balances converted into `base` into a `csv::Writer`.
+    ///
+    /// The base asset and the rates used are written first as `#` comment lines, which is why
+    /// this takes the raw output rather than a `csv::Writer`.
+    pub fn dump_consolidated<W: std::io::Write>(
+        self,
+        mut out: W,
+        rates: &RateTable,
+        base: &Asset,
+        at: Option<Timestamp>,
+    ) -> Result<(), Error> {
+        let consolidation = self.clients_consolidated(rates, base, at)?;
+        writeln!(out, "# base: {base}")?;
+        for rate in &consolidation.rates {
+            writeln!(
+                out,
+                "# rate: {} {} effective {}",
+                rate.pair, rate.rate, rate.effective
+            )?;
+        }
+        let mut writer = Writer::from_writer(out);
+        for record in consolidation.records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns each client's balances converted into `base`, ordered by client id.
+    ///
+    /// Every asset balance is converted with the rate effective at `at` (the latest if `None`),
+    /// summed per client and then rounded with [`fx::round`].
+    pub fn clients_consolidated(
+        &self,
+        rates: &RateTable,
+        base: &Asset,
+        at: Option<Timestamp>,
+    ) -> Result<Consolidation, Error> {
+        let mut used: BTreeMap<String, Rate> = BTreeMap::new();
+        let mut records = Vec::with_capacity(self.clients.len());
+        for (id, data) in &self.clients {
+            let mut available = Decimal::ZERO;
+            let mut held = Decimal::ZERO;
+            for (asset, balance) in data.balances() {
+                let (converted, rate) = rates.convert(balance.available, asset, base, at)?;
+                available += converted;
+                held += rates.convert(balance.held, asset, base, at)?.0;
+                if let Some(rate) = rate {
+                    used.insert(rate.pair.to_string(), rate.clone());
+                }
+            }
+            records.push(
+                ConsolidatedRecord::new(
+                    id.clone(),
+                    base.clone(),
+                    fx::round(available),
+                    fx::round(held),
+                    false,
+                )
+                .with_state(data.state),
+            );
+        }
+        records.sort_by(|a, b| a.client.cmp(&b.client));
+        Ok(Consolidation {
+            records,
+            rates: used.into_values().collect(),
+        })
+    }
+
+    /// Writes the deposits booked into the suspense account into a `csv::Writer`.
+    pub fn dump_suspense<W: std::io::Write>(self, mut writer: Writer<W>) -> Result<(), Error> {
+        for record in self.suspense_ordered() {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the deposits booked into the suspense account, ordered by timestamp and
+    /// transaction id.
+    pub fn suspense_ordered(&self) -> Vec<SuspenseRecord> {
+        let mut vec: Vec<_> = self
+            .suspense
+            .iter()
+            .map(|(tx, suspended)| SuspenseRecord {
+                tx: tx.clone(),
+                client: suspended.deposit.client.clone(),
+                asset: suspended.deposit.asset.clone(),
+                amount: suspended.deposit.amount,
+                timestamp: suspended.timestamp,
+                released: suspended.released,
+            })
+            .collect();
+        vec.sort_by(|a, b| (a.timestamp, &a.tx).cmp(&(b.timestamp, &b.tx)));
+        vec
+    }
+
+    /// Adds funds to the available balance of a client in the default asset without recording
+    /// them anywhere else, breaking the bookkeeping invariants on purpose.
+    #[cfg(feature = "fixtures")]
+    pub fn corrupt_balance(&mut self, client: &ClientId, amount: Decimal) {
+        let client = self.clients.get_mut(client).expect("client to corrupt");
+        client
+            .balance_mut(&Wallet::default(), &self.default_asset)
+            .available += amount;
+    }
+
+    /// Returns the suspense account.
+    pub(crate) fn suspense(&self) -> &FxHashMap<TransactionId, SuspendedDeposit> {
+        &self.suspense
+    }
+
+    /// Returns the running totals of deposits, withdrawals and chargebacks per asset.
+    pub(crate) fn totals(&self) -> &BTreeMap<Asset, Totals> {
+        &self.totals
+    }
+
     /// Processes a transaction of type: deposit.
-    fn process_deposit(&mut self, tx: TransactionId, deposit: Deposit) {
+    fn process_deposit(&mut self, tx: TransactionId, deposit: Deposit, timestamp: Timestamp) {
+        let fee = self.fees.fee(
+            Operation::Deposit,
+            &deposit.asset,
+            deposit.amount,
+            &self.default_asset,
+        );
         // Get or create the client.
-        let client = self.clients.entry(deposit.client).or_default();
-        // Client must not be locked.
-        if client.locked {
+        let client = self.clients.entry(deposit.client.clone()).or_default();
+        // Client state must accept deposits and the deposit cover its fee, otherwise the money
+        // goes to the suspense account.
+        if client.accepts(Operation::Deposit, &self.lock_policy).not() || fee > deposit.amount {
+            self.totals
+                .entry(deposit.asset.clone())
+                .or_default()
+                .deposited += deposit.amount;
+            self.suspense
+                .insert(tx, SuspendedDeposit::new(deposit, timestamp, false));
             return;
         }
+        // Deposits reactivate dormant accounts.
+        if client.state == AccountState::Dormant {
+            client.set_state(StateEvent {
+                state: AccountState::Active,
+                reason: StateReason::Activity,
+                tx: Some(tx.clone()),
+                timestamp,
+            });
+        }
         // Increase available funds and save the transaction in memory.
-        client.available += deposit.amount;
-        self.disputable_transactions.insert(
-            tx,
-            DisputableTransaction::new(deposit, DisputeState::Undisputed),
+        let balance = client.balance_mut(&deposit.wallet, &deposit.asset);
+        balance.available += deposit.amount;
+        balance.total += deposit.amount;
+        client.count(
+            Operation::Deposit,
+            &deposit.asset,
+            deposit.amount,
+            timestamp,
         );
+        self.totals
+            .entry(deposit.asset.clone())
+            .or_default()
+            .deposited += deposit.amount;
+        self.charge_fee(&deposit.client, &deposit.wallet, &deposit.asset, fee);
+        self.disputable_transactions
+            .insert(tx, DisputableTransaction::new(deposit, timestamp));
     }
 
     /// Processes a transaction of type: withdrawal.
-    fn process_withdrawal(&mut self, withdrawal: Withdrawal) {
+    fn process_withdrawal(
+        &mut self,
+        tx: TransactionId,
+        withdrawal: Withdrawal,
+        timestamp: Timestamp,
+    ) {
         // Client must exist.
         if let Some(client) = self.clients.get_mut(&withdrawal.client) {
-            // Client must not be locked.
-            if client.locked {
+            // Client state must accept withdrawals.
+            if client
+                .accepts(Operation::Withdrawal, &self.lock_policy)
+                .not()
+            {
                 return;
             }
-            // Withdraw the money only if it's available.
-            if client.available >= withdrawal.amount {
-                client.available -= withdrawal.amount;
+            // Withdraw the money and its fee only if available, or within the client's credit
+            // line.
+            let fee = self.fees.fee(
+                Operation::Withdrawal,
+                &withdrawal.asset,
+                withdrawal.amount,
+                &self.default_asset,
+            );
+            let debit = withdrawal.amount + fee;
+            let balance = client.wallet_balance(&withdrawal.wallet, &withdrawal.asset);
+            let available = balance.map_or(Decimal::ZERO, Balance::available);
+            if debit > available {
+                let limit = self.credit_limits.limit(
+                    &withdrawal.client,
+                    &withdrawal.asset,
+                    &self.default_asset,
+                );
+                let drawn =
+                    client.drawn(&withdrawal.asset) + (debit - available.max(Decimal::ZERO));
+                if limit.is_zero() || drawn > limit {
+                    return;
+                }
+            } else if balance.is_none() {
+                return;
             }
+            let balance = client.balance_mut(&withdrawal.wallet, &withdrawal.asset);
+            balance.available -= withdrawal.amount;
+            balance.total -= withdrawal.amount;
+            client.count(
+                Operation::Withdrawal,
+                &withdrawal.asset,
+                withdrawal.amount,
+                timestamp,
+            );
+            self.totals
+                .entry(withdrawal.asset.clone())
+                .or_default()
+                .withdrawn += withdrawal.amount;
+            self.charge_fee(
+                &withdrawal.client,
+                &withdrawal.wallet,
+                &withdrawal.asset,
+                fee,
+            );
+            self.withdrawals
+                .insert(tx, CompletedWithdrawal::new(withdrawal, false));
         }
     }
 
-    /// Processes a transaction of type: dispute.
-    fn process_dispute(&mut self, dispute: Dispute) {
+    /// Processes a transaction of type: transfer, atomically debiting a client and crediting
+    /// another one.
+    fn process_transfer(&mut self, tx: TransactionId, transfer: Transfer, timestamp: Timestamp) {
+        // Both clients must exist, and the wallets be different.
+        let (Some(from), Some(to)) = (
+            self.clients.get(&transfer.from),
+            self.clients.get(&transfer.to),
+        ) else {
+            return;
+        };
+        if transfer.from == transfer.to && transfer.from_wallet == transfer.to_wallet {
+            return;
+        }
+        // The source must accept transfers and the recipient deposits.
+        if from.accepts(Operation::Transfer, &self.lock_policy).not()
+            || to.accepts(Operation::Deposit, &self.lock_policy).not()
+        {
+            return;
+        }
+        // Move the money only if it's available, along with the fee.
+        let fee = self.fees.fee(
+            Operation::Transfer,
+            &transfer.asset,
+            transfer.amount,
+            &self.default_asset,
+        );
+        let available = from
+            .wallet_balance(&transfer.from_wallet, &transfer.asset)
+            .map_or(Decimal::ZERO, Balance::available);
+        if transfer.amount <= Decimal::ZERO || transfer.amount + fee > available {
+            return;
+        }
+        let from = self.clients.get_mut(&transfer.from).expect("checked above");
+        let balance = from.balance_mut(&transfer.from_wallet, &transfer.asset);
+        balance.available -= transfer.amount;
+        balance.total -= transfer.amount;
+        from.count(
+            Operation::Transfer,
+            &transfer.asset,
+            transfer.amount,
+            timestamp,
+        );
+        self.charge_fee(&transfer.from, &transfer.from_wallet, &transfer.asset, fee);
+        let to = self.clients.get_mut(&transfer.to).expect("checked above");
+        // Transfers are activity of the recipient, reactivating it if dormant like deposits.
+        to.apply_dormancy(self.dormant_after, timestamp, Some(&tx));
+        if to.state == AccountState::Dormant {
+            to.set_state(StateEvent {
+                state: AccountState::Active,
+                reason: StateReason::Activity,
+                tx: Some(tx.clone()),
+                timestamp,
+            });
+        }
+        to.last_timestamp = to.last_timestamp.max(Some(timestamp));
+        let balance = to.balance_mut(&transfer.to_wallet, &transfer.asset);
+        balance.available += transfer.amount;
+        balance.total += transfer.amount;
+        // The recipient can dispute the transfer like a deposit, but not a move between the
+        // wallets of one client.
+        if transfer.from == transfer.to {
+            return;
+        }
+        let deposit = Deposit::new(
+            transfer.to,
+            transfer.to_wallet,
+            transfer.asset,
+            transfer.amount,
+        );
+        self.disputable_transactions.insert(
+            tx,
+            DisputableTransaction {
+                from: Some((transfer.from, transfer.from_wallet)),
+                ..DisputableTransaction::new(deposit, timestamp)
+            },
+        );
+    }
+
+    /// Processes a transaction of type: refund.
+    fn process_refund(&mut self, refund: Refund, timestamp: Timestamp) {
+        let mut charged = None;
         // Client must exist.
-        if let Some(client) = self.clients.get_mut(&dispute.client) {
-            // The transaction to be disputed must exist.
-            if let Some(disputable_tx) = self.disputable_transactions.get_mut(&dispute.tx) {
-                // And be in the correct state.
-                if matches!(disputable_tx.state, DisputeState::Undisputed).not() {
+        if let Some(client) = self.clients.get_mut(&refund.client) {
+            // Client state must accept refunds.
+            if client.accepts(Operation::Refund, &self.lock_policy).not() {
+                return;
+            }
+            // The deposit to be refunded must exist, and not be a transfer.
+            if let Some(disputable_tx) = self.disputable_transactions.get_mut(&refund.tx) {
+                if disputable_tx.from.is_some() {
                     return;
                 }
-                // Client id must be the same.
-                if disputable_tx.deposit.client != dispute.client {
+                // Client id and asset must be the same.
+                if disputable_tx
+                    .matches(&refund.client, refund.asset.as_ref())
+                    .not()
+                {
                     return;
                 }
-                // Hold the money and change the transaction state.
-                client.available -= disputable_tx.deposit.amount;
-                client.held += disputable_tx.deposit.amount;
-                disputable_tx.state = DisputeState::Disputed;
+                // The amount, all that's left by default, must be neither refunded, disputed
+                // nor charged back.
+                let refundable = disputable_tx.disputable();
+                let amount = refund.amount.unwrap_or(refundable);
+                if amount <= Decimal::ZERO || amount > refundable {
+                    return;
+                }
+                // Return the money only if it's available, along with the fee.
+                let deposit = &disputable_tx.deposit;
+                let asset = &deposit.asset;
+                let fee = self
+                    .fees
+                    .fee(Operation::Refund, asset, amount, &self.default_asset);
+                let balance = client.balance_mut(&deposit.wallet, asset);
+                if balance.available < amount + fee {
+                    return;
+                }
+                balance.available -= amount;
+                balance.total -= amount;
+                client.count(Operation::Refund, asset, amount, timestamp);
+                self.totals.entry(asset.clone()).or_default().refunded += amount;
+                disputable_tx.refunded += amount;
+                charged = Some((deposit.wallet.clone(), asset.clone(), fee));
+            }
+        }
+        if let Some((wallet, asset, fee)) = charged {
+            self.charge_fee(&refund.client, &wallet, &asset, fee);
+        }
+    }
+
+    /// Processes a transaction of type: authorize.
+    fn process_authorize(
+        &mut self,
+        tx: TransactionId,
+        authorization: Authorization,
+        timestamp: Timestamp,
+    ) {
+        // Client must exist.
+        if let Some(client) = self.clients.get_mut(&authorization.client) {
+            // Client state must accept authorizations.
+            if client
+                .accepts(Operation::Authorize, &self.lock_policy)
+                .not()
+            {
+                return;
+            }
+            // The authorization must be new.
+            if self.authorizations.contains_key(&tx) {
+                return;
+            }
+            // Hold the money only if it's available, along with the fee.
+            let fee = self.fees.fee(
+                Operation::Authorize,
+                &authorization.asset,
+                authorization.amount,
+                &self.default_asset,
+            );
+            let available = client
+                .wallet_balance(&authorization.wallet, &authorization.asset)
+                .map_or(Decimal::ZERO, Balance::available);
+            if authorization.amount <= Decimal::ZERO || authorization.amount + fee > available {
+                return;
+            }
+            client.hold(
+                &authorization.wallet,
+                &authorization.asset,
+                HoldReason::Authorization,
+                &tx,
+                authorization.amount,
+            );
+            client.count(
+                Operation::Authorize,
+                &authorization.asset,
+                authorization.amount,
+                timestamp,
+            );
+            self.charge_fee(
+                &authorization.client,
+                &authorization.wallet,
+                &authorization.asset,
+                fee,
+            );
+            self.authorizations
+                .insert(tx, PendingAuthorization::new(authorization));
+        }
+    }
+
+    /// Processes a transaction of type: capture.
+    fn process_capture(&mut self, capture: Capture) {
+        // Client must exist.
+        if let Some(client) = self.clients.get_mut(&capture.client) {
+            // Client state must accept captures.
+            if client.accepts(Operation::Capture, &self.lock_policy).not() {
+                return;
+            }
+            // The authorization to be captured must exist.
+            if let Some(pending) = self.authorizations.get_mut(&capture.tx) {
+                // Client id and asset must be the same.
+                if pending
+                    .matches(&capture.client, capture.asset.as_ref())
+                    .not()
+                {
+                    return;
+                }
+                // The amount, all that's left by default, must still be held.
+                let remaining = pending.remaining();
+                let amount = capture.amount.unwrap_or(remaining);
+                if amount <= Decimal::ZERO || amount > remaining {
+                    return;
+                }
+                // Withdraw the held money.
+                client.settle_hold(HoldReason::Authorization, &capture.tx, amount);
+                let asset = &pending.authorization.asset;
+                self.totals.entry(asset.clone()).or_default().withdrawn += amount;
+                pending.captured += amount;
+            }
+        }
+    }
+
+    /// Processes a transaction of type: void.
+    fn process_void(&mut self, void: Void) {
+        // Client must exist.
+        if let Some(client) = self.clients.get_mut(&void.client) {
+            // Client state must accept voids.
+            if client.accepts(Operation::Void, &self.lock_policy).not() {
+                return;
+            }
+            // The authorization to be voided must exist.
+            if let Some(pending) = self.authorizations.get_mut(&void.tx) {
+                // Client id and asset must be the same.
+                if pending.matches(&void.client, void.asset.as_ref()).not() {
+                    return;
+                }
+                // The amount, all that's left by default, must still be held.
+                let remaining = pending.remaining();
+                let amount = void.amount.unwrap_or(remaining);
+                if amount <= Decimal::ZERO || amount > remaining {
+                    return;
+                }
+                // Release the held money.
+                client.release_hold(HoldReason::Authorization, &void.tx, amount);
+                pending.voided += amount;
+            }
+        }
+    }
+
+    /// Processes a transaction of type: escrow open.
+    fn process_escrow_open(
+        &mut self,
+        tx: TransactionId,
+        opening: EscrowOpening,
+        timestamp: Timestamp,
+    ) {
+        // Both clients must exist and be different.
+        if opening.payer == opening.payee || self.clients.contains_key(&opening.payee).not() {
+            return;
+        }
+        if let Some(payer) = self.clients.get_mut(&opening.payer) {
+            // Payer state must accept escrows.
+            if payer
+                .accepts(Operation::EscrowOpen, &self.lock_policy)
+                .not()
+            {
+                return;
+            }
+            // The escrow must be new.
+            if self.escrows.contains_key(&tx) {
+                return;
+            }
+            // Hold the money only if it's available, along with the fee.
+            let fee = self.fees.fee(
+                Operation::EscrowOpen,
+                &opening.asset,
+                opening.amount,
+                &self.default_asset,
+            );
+            let available = payer
+                .wallet_balance(&opening.payer_wallet, &opening.asset)
+                .map_or(Decimal::ZERO, Balance::available);
+            if opening.amount <= Decimal::ZERO || opening.amount + fee > available {
+                return;
+            }
+            payer.hold(
+                &opening.payer_wallet,
+                &opening.asset,
+                HoldReason::Escrow,
+                &tx,
+                opening.amount,
+            );
+            payer.count(
+                Operation::EscrowOpen,
+                &opening.asset,
+                opening.amount,
+                timestamp,
+            );
+            self.charge_fee(&opening.payer, &opening.payer_wallet, &opening.asset, fee);
+            self.escrows
+                .insert(tx, Escrow::new(opening, timestamp, true));
+        }
+    }
+
+    /// Processes a transaction of type: escrow release.
+    fn process_escrow_release(&mut self, release: EscrowRelease) {
+        // The escrow must be open, and released by its payer.
+        let Some(escrow) = self.escrows.get_mut(&release.tx) else {
+            return;
+        };
+        let opening = &escrow.opening;
+        if escrow.open.not() || opening.payer != release.client {
+            return;
+        }
+        // Payee state must accept deposits.
+        let payee = self.clients.get(&opening.payee).expect("escrow payee");
+        if payee.accepts(Operation::Deposit, &self.lock_policy).not() {
+            return;
+        }
+        // Pay the held money to the payee.
+        let payer = self.clients.get_mut(&opening.payer).expect("escrow payer");
+        payer.settle_hold(HoldReason::Escrow, &release.tx, opening.amount);
+        let payee = self.clients.get_mut(&opening.payee).expect("escrow payee");
+        let balance = payee.balance_mut(&opening.payee_wallet, &opening.asset);
+        balance.available += opening.amount;
+        balance.total += opening.amount;
+        escrow.open = false;
+    }
+
+    /// Processes a transaction of type: escrow cancel.
+    fn process_escrow_cancel(&mut self, cancel: EscrowCancel) {
+        // The escrow must be open, and cancelled by its payer.
+        let Some(escrow) = self.escrows.get_mut(&cancel.tx) else {
+            return;
+        };
+        if escrow.open.not() || escrow.opening.payer != cancel.client {
+            return;
+        }
+        // Give the held money back to the payer.
+        let payer = self.clients.get_mut(&cancel.client).expect("escrow payer");
+        payer.release_hold(HoldReason::Escrow, &cancel.tx, escrow.opening.amount);
+        escrow.open = false;
+    }
+
+    /// Processes a transaction of type: dispute.
+    fn process_dispute(&mut self, dispute: Dispute, timestamp: Timestamp) {
+        // Client must exist.
+        if let Some(client) = self.clients.get_mut(&dispute.client) {
+            // Client state must accept disputes.
+            if client.accepts(Operation::Dispute, &self.lock_policy).not() {
+                return;
+            }
+            // The transaction to be disputed must exist.
+            if let Some(disputable_tx) = self.disputable_transactions.get_mut(&dispute.tx) {
+                // Client id and asset must be the same.
+                if disputable_tx
+                    .matches(&dispute.client, dispute.asset.as_ref())
+                    .not()
+                {
+                    return;
+                }
+                // The amount, all that's left by default, must still be disputable.
+                let disputable = disputable_tx.disputable();
+                let amount = dispute.amount.unwrap_or(disputable);
+                if amount <= Decimal::ZERO || amount > disputable {
+                    return;
+                }
+                // Opening a new dispute cycle must be within the policy's limit.
+                let max_cycles = self.dispute_policy.max_cycles;
+                if disputable_tx.state != DisputeState::Disputed
+                    && max_cycles > 0
+                    && disputable_tx.cycles >= max_cycles
+                {
+                    return;
+                }
+                // Hold the money and change the transaction state.
+                let deposit = &disputable_tx.deposit;
+                client.hold(
+                    &deposit.wallet,
+                    &deposit.asset,
+                    HoldReason::Dispute,
+                    &dispute.tx,
+                    amount,
+                );
+                if disputable_tx.state != DisputeState::Disputed {
+                    disputable_tx.opened_at = timestamp;
+                    disputable_tx.opened_after = self.processed;
+                    self.open_disputes.insert(dispute.tx.clone());
+                }
+                disputable_tx.disputed += amount;
+                disputable_tx.record(
+                    &dispute.tx,
+                    Operation::Dispute,
+                    amount,
+                    Some(dispute.tx.clone()),
+                    timestamp,
+                );
             }
         }
     }
 
     /// Processes a transaction of type: resolve.
-    fn process_resolve(&mut self, resolve: Resolve) {
+    fn process_resolve(
+        &mut self,
+        resolve: Resolve,
+        timestamp: Timestamp,
+        trigger: Option<TransactionId>,
+    ) {
         // Client must exist.
         if let Some(client) = self.clients.get_mut(&resolve.client) {
+            // Client state must accept resolves.
+            if client.accepts(Operation::Resolve, &self.lock_policy).not() {
+                return;
+            }
             // The transaction to be resolved must exist.
             if let Some(disputable_tx) = self.disputable_transactions.get_mut(&resolve.tx) {
-                // And be in the correct state.
-                if matches!(disputable_tx.state, DisputeState::Disputed).not() {
+                // Client id and asset must be the same.
+                if disputable_tx
+                    .matches(&resolve.client, resolve.asset.as_ref())
+                    .not()
+                {
                     return;
                 }
-                // Client id must be the same.
-                if disputable_tx.deposit.client != resolve.client {
+                // The amount, all the disputed one by default, must be under dispute.
+                let amount = resolve.amount.unwrap_or(disputable_tx.disputed);
+                if amount <= Decimal::ZERO || amount > disputable_tx.disputed {
                     return;
                 }
                 // Unblock the money and change the transaction state.
-                client.available += disputable_tx.deposit.amount;
-                client.held -= disputable_tx.deposit.amount;
-                disputable_tx.state = DisputeState::Undisputed;
+                client.release_hold(HoldReason::Dispute, &resolve.tx, amount);
+                disputable_tx.disputed -= amount;
+                disputable_tx.resolved += amount;
+                disputable_tx.record(&resolve.tx, Operation::Resolve, amount, trigger, timestamp);
+                if disputable_tx.state != DisputeState::Disputed {
+                    self.open_disputes.remove(&resolve.tx);
+                }
             }
         }
     }
 
     /// Processes a transaction of type: chargeback.
-    fn process_chargeback(&mut self, chargeback: Chargeback) {
+    fn process_chargeback(
+        &mut self,
+        chargeback: Chargeback,
+        timestamp: Timestamp,
+        trigger: Option<TransactionId>,
+    ) {
+        // Chargebacks of transfers give the money back to the source, which must not be closed.
+        let source_closed = self
+            .disputable_transactions
+            .get(&chargeback.tx)
+            .and_then(|disputable_tx| disputable_tx.from.as_ref())
+            .and_then(|(from, _)| self.clients.get(from))
+            .is_some_and(|source| source.state == AccountState::Closed);
+        if source_closed {
+            return;
+        }
+        let mut penalty = None;
         // Client must exist.
         if let Some(client) = self.clients.get_mut(&chargeback.client) {
+            // Client state must accept chargebacks.
+            if client
+                .accepts(Operation::Chargeback, &self.lock_policy)
+                .not()
+            {
+                return;
+            }
             // The transaction for chargeback must exist.
             if let Some(disputable_tx) = self.disputable_transactions.get_mut(&chargeback.tx) {
-                // And be in the correct state.
-                if matches!(disputable_tx.state, DisputeState::Disputed).not() {
+                // Client id and asset must be the same.
+                if disputable_tx
+                    .matches(&chargeback.client, chargeback.asset.as_ref())
+                    .not()
+                {
+                    return;
+                }
+                // The amount, all the disputed one by default, must be under dispute.
+                let amount = chargeback.amount.unwrap_or(disputable_tx.disputed);
+                if amount <= Decimal::ZERO || amount > disputable_tx.disputed {
+                    return;
+                }
+                // Return the money and change the transaction state.
+                let asset = &disputable_tx.deposit.asset;
+                client.settle_hold(HoldReason::Dispute, &chargeback.tx, amount);
+                // Lock the client once it reaches the policy's number of chargebacks.
+                client.chargebacks += 1;
+                let threshold = self.lock_policy.chargebacks_to_lock;
+                if client.state.can_become(AccountState::Frozen)
+                    && threshold > 0
+                    && client.chargebacks >= threshold
+                {
+                    client.set_state(StateEvent {
+                        state: AccountState::Frozen,
+                        reason: StateReason::Chargebacks(client.chargebacks),
+                        tx: Some(chargeback.tx.clone()),
+                        timestamp,
+                    });
+                }
+                match &disputable_tx.from {
+                    Some((from, wallet)) => {
+                        let source = self.clients.get_mut(from).expect("transfer source");
+                        let balance = source.balance_mut(wallet, asset);
+                        balance.available += amount;
+                        balance.total += amount;
+                    }
+                    None => self.totals.entry(asset.clone()).or_default().charged_back += amount,
+                }
+                disputable_tx.disputed -= amount;
+                disputable_tx.charged_back += amount;
+                disputable_tx.record(
+                    &chargeback.tx,
+                    Operation::Chargeback,
+                    amount,
+                    trigger,
+                    timestamp,
+                );
+                if disputable_tx.state != DisputeState::Disputed {
+                    self.open_disputes.remove(&chargeback.tx);
+                }
+                // The client pays the chargeback fee as a penalty, even below zero.
+                let deposit = &disputable_tx.deposit;
+                let fee = self.fees.fee(
+                    Operation::Chargeback,
+                    &deposit.asset,
+                    amount,
+                    &self.default_asset,
+                );
+                penalty = Some((deposit.wallet.clone(), deposit.asset.clone(), fee));
+            }
+        }
+        if let Some((wallet, asset, fee)) = penalty {
+            self.charge_fee(&chargeback.client, &wallet, &asset, fee);
+        }
+    }
+
+    /// Processes a transaction of type: chargeback reversal.
+    fn process_chargeback_reversal(&mut self, reversal: ChargebackReversal, timestamp: Timestamp) {
+        // Client must exist.
+        if let Some(client) = self.clients.get_mut(&reversal.client) {
+            // Client state must accept chargeback reversals.
+            if client
+                .accepts(Operation::ChargebackReversal, &self.lock_policy)
+                .not()
+            {
+                return;
+            }
+            // The transaction to be reversed must exist.
+            if let Some(disputable_tx) = self.disputable_transactions.get_mut(&reversal.tx) {
+                // Client id and asset must be the same.
+                if disputable_tx
+                    .matches(&reversal.client, reversal.asset.as_ref())
+                    .not()
+                {
                     return;
                 }
-                // Client id must be the same.
-                if disputable_tx.deposit.client != chargeback.client {
+                // Chargebacks of transfers are settled internally, with no representment, and
+                // deposits reversed by an operator no longer belong to the client.
+                if disputable_tx.from.is_some() || disputable_tx.operator_reversed {
                     return;
                 }
-                // Return the money, lock the client and change the transaction state.
-                client.held -= disputable_tx.deposit.amount;
-                client.locked = true;
-                disputable_tx.state = DisputeState::Chargedback;
+                // The amount, all that's charged back by default, must not be reversed yet.
+                let reversible = disputable_tx.charged_back - disputable_tx.reversed;
+                let amount = reversal.amount.unwrap_or(reversible);
+                if amount <= Decimal::ZERO || amount > reversible {
+                    return;
+                }
+                // Give the money back and change the transaction state.
+                let deposit = &disputable_tx.deposit;
+                let asset = &deposit.asset;
+                let balance = client.balance_mut(&deposit.wallet, asset);
+                balance.available += amount;
+                balance.total += amount;
+                self.totals.entry(asset.clone()).or_default().reversed += amount;
+                disputable_tx.reversed += amount;
+                disputable_tx.record(
+                    &reversal.tx,
+                    Operation::ChargebackReversal,
+                    amount,
+                    Some(reversal.tx.clone()),
+                    timestamp,
+                );
+                // The chargeback no longer counts, which may unlock the client.
+                client.chargebacks = client.chargebacks.saturating_sub(1);
+                let locked_by_chargebacks = client.locked()
+                    && matches!(client.state_reason(), Some(StateReason::Chargebacks(_)));
+                if self.lock_policy.reversal_unlocks
+                    && locked_by_chargebacks
+                    && client.chargebacks < self.lock_policy.chargebacks_to_lock
+                {
+                    client.set_state(StateEvent {
+                        state: AccountState::Active,
+                        reason: StateReason::ChargebackReversal,
+                        tx: Some(reversal.tx.clone()),
+                        timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Resolves or charges back, as the dispute policy says, the disputes open for too long.
+    fn expire_disputes(&mut self, now: Timestamp, trigger: Option<&TransactionId>) {
+        let policy = self.dispute_policy;
+        if policy.expire_after.is_none() && policy.expire_after_transactions.is_none() {
+            return;
+        }
+        let expired: Vec<_> = self
+            .open_disputes
+            .iter()
+            .filter(|tx| {
+                let disputable_tx = &self.disputable_transactions[*tx];
+                let age = now
+                    .as_secs()
+                    .saturating_sub(disputable_tx.opened_at.as_secs());
+                // The transaction opening the dispute isn't a subsequent one.
+                let subsequent = self.processed - disputable_tx.opened_after - 1;
+                policy.expire_after.is_some_and(|secs| age >= secs)
+                    || policy
+                        .expire_after_transactions
+                        .is_some_and(|count| subsequent >= count)
+            })
+            .cloned()
+            .collect();
+        for tx in expired {
+            let deposit = &self.disputable_transactions[&tx].deposit;
+            let (client, asset) = (deposit.client.clone(), Some(deposit.asset.clone()));
+            match policy.on_expiry {
+                ExpiryAction::Resolve => self.process_resolve(
+                    Resolve::new(client, tx, asset, None),
+                    now,
+                    trigger.cloned(),
+                ),
+                ExpiryAction::Chargeback => self.process_chargeback(
+                    Chargeback::new(client, tx, asset, None),
+                    now,
+                    trigger.cloned(),
+                ),
+            }
+        }
+    }
+
+    /// Processes an administrative state change, returning whether it was applied.
+    fn process_transition(&mut self, client: &ClientId, event: StateEvent) -> bool {
+        // Client must exist and be allowed to move into the new state.
+        match self.clients.get_mut(client) {
+            Some(client) if client.state.can_become(event.state) => {
+                client.set_state(event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Processes an administrative closure, returning whether it was applied.
+    fn process_close(
+        &mut self,
+        client_id: &ClientId,
+        tx: &TransactionId,
+        event: StateEvent,
+        payouts: &mut Vec<(Wallet, Asset, Decimal)>,
+    ) -> bool {
+        // Client must exist and be allowed to close.
+        let Some(client) = self.clients.get_mut(client_id) else {
+            return false;
+        };
+        if client.state.can_become(AccountState::Closed).not() {
+            return false;
+        }
+        // No funds may be held, nor owed.
+        if client
+            .wallet_balances()
+            .any(|(_, _, balance)| !balance.held.is_zero() || balance.available < Decimal::ZERO)
+        {
+            return false;
+        }
+        // Post the interest accrued so far, the remainder below the posted precision being
+        // forfeited.
+        client.post_interest(
+            client_id,
+            tx,
+            event.timestamp,
+            &mut self.totals,
+            &mut self.interest,
+        );
+        client.accrued.clear();
+        // Pay out the remaining balance of every wallet.
+        for (wallet, balances) in &mut client.wallets {
+            for (asset, balance) in balances {
+                if balance.available.is_zero() {
+                    continue;
+                }
+                self.totals.entry(asset.clone()).or_default().withdrawn += balance.available;
+                payouts.push((wallet.clone(), asset.clone(), balance.available));
+                balance.available = Decimal::ZERO;
+                balance.total = Decimal::ZERO;
+            }
+        }
+        client.set_state(event);
+        true
+    }
+
+    /// Counts the interest earned by the available balances of the days before the one of
+    /// `now`, into the accrued interest of each client.
+    ///
+    /// Balances only change with records, so counting before each record accrues every balance
+    /// for the days it was held at the end of.
+    fn count_interest(&mut self, now: Timestamp) {
+        let today = now.day_start();
+        let Some(since) = self.interest_counted_until else {
+            self.interest_counted_until = Some(today);
+            return;
+        };
+        if today <= since || self.interest_rates.is_empty() {
+            return;
+        }
+        self.interest_counted_until = Some(today);
+        for (id, client) in &mut self.clients {
+            // Closed accounts and the house account earn no interest.
+            if client.state == AccountState::Closed || self.house_account.as_ref() == Some(id) {
+                continue;
+            }
+            let tier = self.tiers.tier(id);
+            for (wallet, balances) in &client.wallets {
+                for (asset, balance) in balances {
+                    if balance.available <= Decimal::ZERO {
+                        continue;
+                    }
+                    let Some(rate) = self.interest_rates.lookup(tier, asset, &self.default_asset)
+                    else {
+                        continue;
+                    };
+                    *client
+                        .accrued
+                        .entry((wallet.clone(), asset.clone()))
+                        .or_default() += rate.interest(balance.available, since, today);
+                }
+            }
+        }
+    }
+
+    /// Processes an interest run, returning whether it was applied.
+    ///
+    /// Each run credits the interest accrued since the previous run, rounded, the remainder
+    /// being posted by the next runs.
+    fn process_accrue(&mut self, tx: &TransactionId, at: Timestamp) -> bool {
+        let mut postings = Vec::new();
+        for (id, client) in &mut self.clients {
+            // Closed accounts and the house account earn no interest.
+            if client.state == AccountState::Closed || self.house_account.as_ref() == Some(id) {
+                continue;
+            }
+            client.post_interest(id, tx, at, &mut self.totals, &mut postings);
+        }
+        postings.sort_by(|a, b| {
+            (&a.client, &a.wallet, &a.asset).cmp(&(&b.client, &b.wallet, &b.asset))
+        });
+        self.interest.extend(postings);
+        true
+    }
+
+    /// Processes the merge of a duplicate account into another one, returning whether it was
+    /// applied.
+    fn process_merge(
+        &mut self,
+        source_id: &ClientId,
+        target_id: &ClientId,
+        event: StateEvent,
+    ) -> bool {
+        // Both clients must exist and be different, the target must not be closed.
+        if source_id == target_id
+            || self
+                .clients
+                .get(target_id)
+                .is_none_or(|target| target.state == AccountState::Closed)
+        {
+            return false;
+        }
+        // The source must be allowed to close.
+        let Some(source) = self.clients.get_mut(source_id) else {
+            return false;
+        };
+        if source.state.can_become(AccountState::Closed).not() {
+            return false;
+        }
+        // Move the balances, holds and accrued interest to the target, closing the source.
+        let mut balances = Vec::new();
+        for (wallet, assets) in source.wallets.iter_mut() {
+            for (asset, balance) in assets.iter_mut() {
+                balances.push((wallet.clone(), asset.clone(), std::mem::take(balance)));
+            }
+        }
+        let holds = std::mem::take(&mut source.holds);
+        let accrued = std::mem::take(&mut source.accrued);
+        source.set_state(event);
+        let target = self.clients.get_mut(target_id).expect("merge target");
+        for (wallet, asset, balance) in balances {
+            *target.balance_mut(&wallet, &asset) += balance;
+        }
+        target.holds.extend(holds);
+        for (key, interest) in accrued {
+            *target.accrued.entry(key).or_default() += interest;
+        }
+
+        // Re-point the transactions of the source to the target.
+        for disputable_tx in self.disputable_transactions.values_mut() {
+            if disputable_tx.deposit.client == *source_id {
+                disputable_tx.deposit.client = target_id.clone();
+            }
+            if let Some((from, _)) = &mut disputable_tx.from {
+                if from == source_id {
+                    *from = target_id.clone();
+                }
+            }
+        }
+        for pending in self.authorizations.values_mut() {
+            if pending.authorization.client == *source_id {
+                pending.authorization.client = target_id.clone();
+            }
+        }
+        for completed in self.withdrawals.values_mut() {
+            if completed.withdrawal.client == *source_id {
+                completed.withdrawal.client = target_id.clone();
+            }
+        }
+        for escrow in self.escrows.values_mut() {
+            if escrow.opening.payer == *source_id {
+                escrow.opening.payer = target_id.clone();
+            }
+            if escrow.opening.payee == *source_id {
+                escrow.opening.payee = target_id.clone();
+            }
+        }
+        for suspended in self.suspense.values_mut() {
+            if suspended.deposit.client == *source_id {
+                suspended.deposit.client = target_id.clone();
             }
         }
+        self.merged.insert(source_id.clone(), target_id.clone());
+        true
+    }
+
+    /// Returns the client an account was merged into, following successive merges, or the
+    /// client itself.
+    fn merge_target(&self, mut client: ClientId) -> ClientId {
+        while let Some(target) = self.merged.get(&client) {
+            client = target.clone();
+        }
+        client
+    }
+
+    /// Checks a funds movement against the limits of its client, logging it if refused.
+    fn within_limits(&mut self, record: &TransactionRecord, timestamp: Timestamp) -> bool {
+        // Refunds are in the asset of their deposit, all that's refundable by default.
+        let refunded = (record.r#type == Operation::Refund)
+            .then(|| self.disputable_transactions.get(&record.tx))
+            .flatten();
+        let asset = match (&record.asset, refunded) {
+            (Some(asset), _) => asset.clone(),
+            (None, Some(refunded)) => refunded.deposit.asset.clone(),
+            (None, None) => self.default_asset.clone(),
+        };
+        let amount = record
+            .amount
+            .or(refunded.map(DisputableTransaction::disputable))
+            .unwrap_or_default();
+
+        let tier = self.tiers.tier(&record.client);
+        let Some(limits) = self
+            .limits
+            .lookup(&record.client, tier, &asset, &self.default_asset)
+        else {
+            return true;
+        };
+        let usage = self
+            .clients
+            .get(&record.client)
+            .and_then(|client| client.usage(&asset))
+            .cloned()
+            .unwrap_or_default();
+        match usage.check(limits, record.r#type.debits(), amount, timestamp) {
+            Ok(()) => true,
+            Err(reason) => {
+                self.refusals.push(Refusal {
+                    tx: record.tx.clone(),
+                    client: record.client.clone(),
+                    r#type: record.r#type,
+                    asset,
+                    amount,
+                    reason,
+                    timestamp,
+                });
+                false
+            }
+        }
+    }
+
+    /// Moves a fee from a client wallet to the house revenue account.
+    fn charge_fee(&mut self, client: &ClientId, wallet: &Wallet, asset: &Asset, fee: Decimal) {
+        // The house account pays no fees to itself.
+        if fee.is_zero() || self.house_account.as_ref() == Some(client) {
+            return;
+        }
+        let client = self.clients.get_mut(client).expect("charged client");
+        let balance = client.balance_mut(wallet, asset);
+        balance.available -= fee;
+        balance.total -= fee;
+        let house = self
+            .house_account
+            .clone()
+            .expect("fees are set along with the house account");
+        let house = self.clients.entry(house).or_default();
+        let balance = house.balance_mut(&Wallet::default(), asset);
+        balance.available += fee;
+        balance.total += fee;
+    }
+
+    /// Returns the overdraft limit of a client in the default asset.
+    fn credit_limit(&self, client: &ClientId) -> Decimal {
+        self.credit_limits
+            .limit(client, &self.default_asset, &self.default_asset)
+    }
+
+    /// Processes the release of a suspended deposit, returning whether it was applied.
+    fn process_release(&mut self, client_id: &ClientId, tx: &TransactionId) -> bool {
+        // The deposit must be in the suspense account, for the same client.
+        let Some(suspended) = self.suspense.get_mut(tx) else {
+            return false;
+        };
+        if suspended.released || suspended.deposit.client != *client_id {
+            return false;
+        }
+        // Client state must now accept deposits.
+        let Some(client) = self.clients.get_mut(client_id) else {
+            return false;
+        };
+        if client.accepts(Operation::Deposit, &self.lock_policy).not() {
+            return false;
+        }
+        // The deposit pays its fee when released, and stays suspended if it can't cover it.
+        let deposit = &suspended.deposit;
+        let fee = self.fees.fee(
+            Operation::Deposit,
+            &deposit.asset,
+            deposit.amount,
+            &self.default_asset,
+        );
+        if fee > deposit.amount {
+            return false;
+        }
+        // Credit the client, the deposit becoming disputable.
+        let balance = client.balance_mut(&deposit.wallet, &deposit.asset);
+        balance.available += deposit.amount;
+        balance.total += deposit.amount;
+        suspended.released = true;
+        let deposit = Deposit::new(
+            deposit.client.clone(),
+            deposit.wallet.clone(),
+            deposit.asset.clone(),
+            deposit.amount,
+        );
+        let timestamp = suspended.timestamp;
+        self.charge_fee(&deposit.client, &deposit.wallet, &deposit.asset, fee);
+        self.disputable_transactions
+            .insert(tx.clone(), DisputableTransaction::new(deposit, timestamp));
+        true
+    }
+
+    /// Marks as dormant the active accounts without transactions for the dormancy period, the
+    /// house account excepted.
+    pub fn apply_dormancy(&mut self, now: Timestamp) {
+        for (id, client) in &mut self.clients {
+            if self.house_account.as_ref() != Some(id) {
+                client.apply_dormancy(self.dormant_after, now, None);
+            }
+        }
+    }
+
+    /// Processes an administrative reversal of a deposit or withdrawal, returning whether it
+    /// was applied.
+    fn process_reversal(
+        &mut self,
+        client_id: &ClientId,
+        tx: &TransactionId,
+        timestamp: Timestamp,
+    ) -> bool {
+        // Reversals bypass the lock and the available funds checks, not the closure.
+        let Some(client) = self.clients.get_mut(client_id) else {
+            return false;
+        };
+        if client.state == AccountState::Closed {
+            return false;
+        }
+        if let Some(disputable_tx) = self.disputable_transactions.get_mut(tx) {
+            // Only deposits of the same client, neither reversed nor in dispute.
+            if disputable_tx.from.is_some()
+                || disputable_tx.deposit.client != *client_id
+                || disputable_tx.operator_reversed
+                || disputable_tx.state == DisputeState::Disputed
+            {
+                return false;
+            }
+            // Take back what's still credited of the deposit.
+            let amount = disputable_tx.credited();
+            let deposit = &disputable_tx.deposit;
+            let asset = &deposit.asset;
+            let balance = client.balance_mut(&deposit.wallet, asset);
+            balance.available -= amount;
+            balance.total -= amount;
+            self.totals.entry(asset.clone()).or_default().deposited -= amount;
+            disputable_tx.operator_reversed = true;
+            disputable_tx.record(tx, Operation::Reversal, amount, Some(tx.clone()), timestamp);
+            true
+        } else if let Some(completed) = self.withdrawals.get_mut(tx) {
+            // Only withdrawals of the same client, not reversed yet.
+            let withdrawal = &completed.withdrawal;
+            if withdrawal.client != *client_id || completed.reversed {
+                return false;
+            }
+            // Give the money back.
+            let balance = client.balance_mut(&withdrawal.wallet, &withdrawal.asset);
+            balance.available += withdrawal.amount;
+            balance.total += withdrawal.amount;
+            self.totals
+                .entry(withdrawal.asset.clone())
+                .or_default()
+                .withdrawn -= withdrawal.amount;
+            completed.reversed = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Processes an administrative adjustment, returning whether it was applied.
+    fn process_adjust(&mut self, adjustment: Adjustment) -> bool {
+        // Adjustments bypass the lock and the available funds checks, not the closure.
+        let client = self.clients.entry(adjustment.client).or_default();
+        if client.state == AccountState::Closed {
+            return false;
+        }
+        let balance = client.balance_mut(&adjustment.wallet, &adjustment.asset);
+        balance.available += adjustment.amount;
+        balance.total += adjustment.amount;
+        self.totals.entry(adjustment.asset).or_default().adjusted += adjustment.amount;
+        true
     }
 }
 
 /// Id of a client.
-#[derive(Deserialize, Serialize, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Debug, Hash)]
-pub struct ClientId(u16);
+#[derive(Deserialize, Serialize, Clone, Eq, PartialEq, PartialOrd, Ord, Debug, Hash)]
+pub struct ClientId(Identifier);
 
-/// Data for a client.
+impl<T: Into<Identifier>> From<T> for ClientId {
+    fn from(value: T) -> Self {
+        Self(value.into())
+    }
+}
+
+impl ClientId {
+    pub(crate) fn into_kind(self, kind: IdKind) -> Result<Self, Error> {
+        self.0.into_kind(kind).map(Self)
+    }
+}
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Balance of a client in one asset.
 #[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq, Default)]
-pub struct ClientData {
+pub struct Balance {
     available: Decimal,
     held: Decimal,
-    locked: bool,
+    total: Decimal,
+}
+
+impl Balance {
+    /// Funds available for withdrawal.
+    pub fn available(&self) -> Decimal {
+        self.available
+    }
+
+    /// Funds held, the sum of the client's holds in the asset.
+    pub fn held(&self) -> Decimal {
+        self.held
+    }
+
+    /// Total funds, tracked independently from available and held funds.
+    pub fn total(&self) -> Decimal {
+        self.total
+    }
+
+    /// Funds drawn below zero, on credit.
+    fn drawn(&self) -> Decimal {
+        // Negating zero available funds would report `-0`.
+        if self.available < Decimal::ZERO {
+            -self.available
+        } else {
+            Decimal::ZERO
+        }
+    }
+}
+
+impl AddAssign for Balance {
+    fn add_assign(&mut self, other: Self) {
+        self.available += other.available;
+        self.held += other.held;
+        self.total += other.total;
+    }
+}
+
+/// Data for a client.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq, Default)]
+pub struct ClientData {
+    wallets: BTreeMap<Wallet, BTreeMap<Asset, Balance>>,
+    state: AccountState,
+    state_history: Vec<StateEvent>,
+    chargebacks: u32,
+    last_timestamp: Option<Timestamp>,
+    holds: Vec<Hold>,
+    usage: BTreeMap<Asset, Usage>,
+    accrued: BTreeMap<(Wallet, Asset), Decimal>,
+}
+
+impl ClientData {
+    /// Returns the balance in the given asset over all wallets, if the client ever held it.
+    pub fn balance(&self, asset: &Asset) -> Option<Balance> {
+        self.wallets
+            .values()
+            .filter_map(|balances| balances.get(asset).copied())
+            .reduce(|mut sum, balance| {
+                sum += balance;
+                sum
+            })
+    }
+
+    /// Returns the balances over all wallets of the assets the client ever held, ordered by
+    /// asset.
+    pub fn balances(&self) -> impl Iterator<Item = (&Asset, Balance)> {
+        let mut sums: BTreeMap<&Asset, Balance> = BTreeMap::new();
+        for (asset, balance) in self.wallets.values().flatten() {
+            *sums.entry(asset).or_default() += *balance;
+        }
+        sums.into_iter()
+    }
+
+    /// Returns the balance of a wallet in the given asset, if the wallet ever held it.
+    pub fn wallet_balance(&self, wallet: &Wallet, asset: &Asset) -> Option<&Balance> {
+        self.wallets.get(wallet)?.get(asset)
+    }
+
+    /// Returns the balances of every wallet in every asset, ordered by wallet and asset.
+    pub fn wallet_balances(&self) -> impl Iterator<Item = (&Wallet, &Asset, &Balance)> {
+        self.wallets.iter().flat_map(|(wallet, balances)| {
+            balances
+                .iter()
+                .map(move |(asset, balance)| (wallet, asset, balance))
+        })
+    }
+
+    /// Whether the client uses other wallets than the `main` one.
+    pub fn has_wallets(&self) -> bool {
+        self.wallets.keys().any(|wallet| !wallet.is_main())
+    }
+
+    /// Returns the running counters of the client's transactions in the given asset, if any.
+    pub fn usage(&self, asset: &Asset) -> Option<&Usage> {
+        self.usage.get(asset)
+    }
+
+    /// Returns the interest accrued by a wallet in an asset and not posted yet, unrounded.
+    pub fn accrued(&self, wallet: &Wallet, asset: &Asset) -> Decimal {
+        self.accrued
+            .get(&(wallet.clone(), asset.clone()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Credits the accrued interest rounded, keeping the remainder, and records its postings.
+    fn post_interest(
+        &mut self,
+        id: &ClientId,
+        tx: &TransactionId,
+        at: Timestamp,
+        totals: &mut BTreeMap<Asset, Totals>,
+        postings: &mut Vec<InterestPosting>,
+    ) {
+        for ((wallet, asset), accrued) in &mut self.accrued {
+            let interest = round_interest(*accrued);
+            if interest <= Decimal::ZERO {
+                continue;
+            }
+            *accrued -= interest;
+            let balance = self
+                .wallets
+                .entry(wallet.clone())
+                .or_default()
+                .entry(asset.clone())
+                .or_default();
+            balance.available += interest;
+            balance.total += interest;
+            totals.entry(asset.clone()).or_default().interest += interest;
+            postings.push(InterestPosting {
+                tx: tx.clone(),
+                client: id.clone(),
+                wallet: wallet.clone(),
+                asset: asset.clone(),
+                amount: interest,
+                timestamp: at,
+            });
+        }
+    }
+
+    /// Returns the credit drawn in the given asset: the funds below zero over all wallets.
+    pub fn drawn(&self, asset: &Asset) -> Decimal {
+        self.wallets
+            .values()
+            .filter_map(|balances| balances.get(asset))
+            .map(Balance::drawn)
+            .sum()
+    }
+
+    /// Whether the account is locked (frozen).
+    pub fn locked(&self) -> bool {
+        self.state.is_locked()
+    }
+
+    /// State of the account.
+    pub fn state(&self) -> AccountState {
+        self.state
+    }
+
+    /// Why the account is in its current state, `None` if it never changed.
+    pub fn state_reason(&self) -> Option<&StateReason> {
+        self.state_history.last().map(|event| &event.reason)
+    }
+
+    /// All the state changes of the account, oldest first.
+    pub fn state_history(&self) -> &[StateEvent] {
+        &self.state_history
+    }
+
+    /// Number of chargebacks of the account.
+    pub fn chargebacks(&self) -> u32 {
+        self.chargebacks
+    }
+
+    /// Time of the latest transaction of the client.
+    pub fn last_timestamp(&self) -> Option<Timestamp> {
+        self.last_timestamp
+    }
+
+    /// The individual holds making up the held funds, oldest first.
+    pub fn holds(&self) -> &[Hold] {
+        &self.holds
+    }
+
+    /// Whether the account state accepts a client operation.
+    fn accepts(&self, operation: Operation, lock_policy: &LockPolicy) -> bool {
+        match self.state {
+            AccountState::Active => true,
+            AccountState::Restricted | AccountState::Dormant => !operation.debits(),
+            AccountState::Frozen => match operation {
+                Operation::Deposit => !lock_policy.blocks_deposits,
+                operation if operation.debits() => !lock_policy.blocks_withdrawals,
+                Operation::Dispute => !lock_policy.blocks_disputes,
+                Operation::Resolve | Operation::Chargeback => lock_policy.resolves_pending_disputes,
+                _ => true,
+            },
+            AccountState::Closed => false,
+        }
+    }
+
+    /// Counts an applied funds movement in the running counters.
+    fn count(&mut self, operation: Operation, asset: &Asset, amount: Decimal, at: Timestamp) {
+        self.usage
+            .entry(asset.clone())
+            .or_default()
+            .record(operation.debits(), amount, at);
+    }
+
+    fn set_state(&mut self, event: StateEvent) {
+        self.state = event.state;
+        self.state_history.push(event);
+    }
+
+    /// Marks the account dormant if active and without transactions for `dormant_after` seconds.
+    fn apply_dormancy(
+        &mut self,
+        dormant_after: Option<u64>,
+        now: Timestamp,
+        tx: Option<&TransactionId>,
+    ) {
+        let (Some(period), Some(last)) = (dormant_after, self.last_timestamp) else {
+            return;
+        };
+        if self.state == AccountState::Active
+            && now.as_secs().saturating_sub(last.as_secs()) >= period
+        {
+            self.set_state(StateEvent {
+                state: AccountState::Dormant,
+                reason: StateReason::Inactivity,
+                tx: tx.cloned(),
+                timestamp: now,
+            });
+        }
+    }
+
+    fn balance_mut(&mut self, wallet: &Wallet, asset: &Asset) -> &mut Balance {
+        self.wallets
+            .entry(wallet.clone())
+            .or_default()
+            .entry(asset.clone())
+            .or_default()
+    }
+
+    /// Holds available funds of a wallet for a reason and transaction.
+    fn hold(
+        &mut self,
+        wallet: &Wallet,
+        asset: &Asset,
+        reason: HoldReason,
+        tx: &TransactionId,
+        amount: Decimal,
+    ) {
+        let balance = self.balance_mut(wallet, asset);
+        balance.available -= amount;
+        balance.held += amount;
+        match self
+            .holds
+            .iter_mut()
+            .find(|hold| hold.reason == reason && hold.tx == *tx)
+        {
+            Some(hold) => hold.amount += amount,
+            None => self.holds.push(Hold {
+                wallet: wallet.clone(),
+                asset: asset.clone(),
+                reason,
+                tx: tx.clone(),
+                amount,
+            }),
+        }
+    }
+
+    /// Releases held funds back to the available ones of their wallet.
+    fn release_hold(&mut self, reason: HoldReason, tx: &TransactionId, amount: Decimal) {
+        let balance = self.reduce_hold(reason, tx, amount);
+        balance.held -= amount;
+        balance.available += amount;
+    }
+
+    /// Takes held funds out of the account.
+    fn settle_hold(&mut self, reason: HoldReason, tx: &TransactionId, amount: Decimal) {
+        let balance = self.reduce_hold(reason, tx, amount);
+        balance.held -= amount;
+        balance.total -= amount;
+    }
+
+    /// Reduces a hold, returning the balance holding the funds.
+    fn reduce_hold(
+        &mut self,
+        reason: HoldReason,
+        tx: &TransactionId,
+        amount: Decimal,
+    ) -> &mut Balance {
+        let index = self
+            .holds
+            .iter()
+            .position(|hold| hold.reason == reason && hold.tx == *tx)
+            .expect("held funds must have a hold");
+        self.holds[index].amount -= amount;
+        let hold = if self.holds[index].amount.is_zero() {
+            self.holds.remove(index)
+        } else {
+            self.holds[index].clone()
+        };
+        self.balance_mut(&hold.wallet, &hold.asset)
+    }
 }
 
 /// Record with all client information.
 #[derive(Serialize, Debug, Eq, PartialEq)]
 pub struct ClientRecord {
     client: ClientId,
+    wallet: Option<Wallet>,
     available: Decimal,
     held: Decimal,
     total: Decimal,
+    credit: Decimal,
     locked: bool,
+    state: AccountState,
 }
 
 impl ClientRecord {
-    fn from_id_and_data(client: ClientId, data: ClientData) -> Self {
-        Self::new(client.0, data.available, data.held, data.locked)
+    fn from_id_and_data(
+        client: ClientId,
+        data: &ClientData,
+        asset: &Asset,
+        limit: Decimal,
+    ) -> Self {
+        let balance = data.balance(asset).unwrap_or_default();
+        Self::new(client, balance.available, balance.held, data.locked())
+            .with_state(data.state)
+            .with_credit(data.drawn(asset).min(limit))
+    }
+
+    fn from_wallet(
+        client: ClientId,
+        data: &ClientData,
+        wallet: &Wallet,
+        asset: &Asset,
+        limit: Decimal,
+    ) -> Self {
+        let balance = data
+            .wallet_balance(wallet, asset)
+            .copied()
+            .unwrap_or_default();
+        Self::new(client, balance.available, balance.held, data.locked())
+            .with_state(data.state)
+            .with_wallet(wallet.clone())
+            .with_credit(balance.drawn().min(limit))
+    }
+
+    /// Creates a new client record, aggregating all the client's wallets.
+    pub fn new(
+        client: impl Into<ClientId>,
+        available: Decimal,
+        held: Decimal,
+        locked: bool,
+    ) -> Self {
+        Self {
+            client: client.into(),
+            wallet: None,
+            available,
+            held,
+            total: available + held,
+            credit: Decimal::ZERO,
+            locked: false,
+            state: AccountState::default(),
+        }
+        .with_state(AccountState::from_locked(locked))
+    }
+
+    /// Sets the state of the client account, which the `locked` column follows.
+    pub fn with_state(mut self, state: AccountState) -> Self {
+        self.locked = state.is_locked();
+        self.state = state;
+        self
+    }
+
+    /// Sets the credit drawn by the client, below zero available funds.
+    pub fn with_credit(mut self, credit: Decimal) -> Self {
+        self.credit = credit;
+        self
+    }
+
+    /// Restricts the record to one wallet of the client.
+    pub fn with_wallet(mut self, wallet: impl Into<Wallet>) -> Self {
+        self.wallet = Some(wallet.into());
+        self
+    }
+}
+
+/// Record with the client information for one asset.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct AssetRecord {
+    client: ClientId,
+    asset: Asset,
+    available: Decimal,
+    held: Decimal,
+    total: Decimal,
+    locked: bool,
+    state: AccountState,
+}
+
+impl AssetRecord {
+    fn from_balance(client: ClientId, asset: Asset, balance: Balance, state: AccountState) -> Self {
+        Self::new(client, asset, balance.available, balance.held, false).with_state(state)
+    }
+
+    /// Creates a new asset record.
+    pub fn new(
+        client: impl Into<ClientId>,
+        asset: impl Into<Asset>,
+        available: Decimal,
+        held: Decimal,
+        locked: bool,
+    ) -> Self {
+        Self {
+            client: client.into(),
+            asset: asset.into(),
+            available,
+            held,
+            total: available + held,
+            locked: false,
+            state: AccountState::default(),
+        }
+        .with_state(AccountState::from_locked(locked))
+    }
+
+    /// Sets the state of the account holding the asset.
+    pub fn with_state(mut self, state: AccountState) -> Self {
+        self.locked = state.is_locked();
+        self.state = state;
+        self
+    }
+}
+
+/// Interest credited to a client wallet by an interest run.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct InterestPosting {
+    tx: TransactionId,
+    client: ClientId,
+    wallet: Wallet,
+    asset: Asset,
+    amount: Decimal,
+    timestamp: Timestamp,
+}
+
+impl InterestPosting {
+    /// The client credited.
+    pub fn client(&self) -> &ClientId {
+        &self.client
+    }
+
+    /// The interest credited.
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// Transaction refused for breaching a limit of its client.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Refusal {
+    tx: TransactionId,
+    client: ClientId,
+    r#type: Operation,
+    asset: Asset,
+    amount: Decimal,
+    reason: LimitBreach,
+    timestamp: Timestamp,
+}
+
+impl Refusal {
+    /// The refused transaction.
+    pub fn tx(&self) -> &TransactionId {
+        &self.tx
+    }
+
+    /// The breached limit.
+    pub fn reason(&self) -> LimitBreach {
+        self.reason
+    }
+}
+
+/// Entry of the log of administrative operations.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct AdminEntry {
+    tx: TransactionId,
+    client: ClientId,
+    r#type: Operation,
+    wallet: Option<Wallet>,
+    asset: Option<Asset>,
+    amount: Option<Decimal>,
+    operator: String,
+    reason: String,
+    timestamp: Timestamp,
+}
+
+impl AdminEntry {
+    /// The operator who performed the operation.
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    /// Why the operation was performed.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// The type of the operation.
+    pub fn operation(&self) -> Operation {
+        self.r#type
+    }
+
+    /// The wallet the operation applied to, if it names one.
+    pub fn wallet(&self) -> Option<&Wallet> {
+        self.wallet.as_ref()
+    }
+
+    /// The asset the operation applied to, if it names one.
+    pub fn asset(&self) -> Option<&Asset> {
+        self.asset.as_ref()
+    }
+
+    /// The amount of the operation, for closes the amount paid out.
+    pub fn amount(&self) -> Option<Decimal> {
+        self.amount
+    }
+}
+
+/// Record of a deposit booked into the suspense account.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct SuspenseRecord {
+    tx: TransactionId,
+    client: ClientId,
+    asset: Asset,
+    amount: Decimal,
+    timestamp: Timestamp,
+    released: bool,
+}
+
+impl SuspenseRecord {
+    /// The suspended deposit.
+    pub fn tx(&self) -> &TransactionId {
+        &self.tx
+    }
+
+    /// The client the deposit was made to.
+    pub fn client(&self) -> &ClientId {
+        &self.client
+    }
+
+    /// The amount of the deposit.
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    /// Whether the deposit was released to its client.
+    pub fn released(&self) -> bool {
+        self.released
+    }
+}
+
+/// Funds held on a client account for one asset and reason.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct HoldRecord {
+    client: ClientId,
+    asset: Asset,
+    reason: HoldReason,
+    holds: usize,
+    held: Decimal,
+}
+
+impl HoldRecord {
+    /// Creates a new hold record.
+    pub fn new(
+        client: impl Into<ClientId>,
+        asset: impl Into<Asset>,
+        reason: HoldReason,
+        holds: usize,
+        held: Decimal,
+    ) -> Self {
+        Self {
+            client: client.into(),
+            asset: asset.into(),
+            reason,
+            holds,
+            held,
+        }
+    }
+}
+
+/// Funds held by the open disputes of one asset and age bracket.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct AgingRecord {
+    asset: Asset,
+    age: DisputeAge,
+    disputes: usize,
+    held: Decimal,
+}
+
+impl AgingRecord {
+    /// Creates a new aging record.
+    pub fn new(asset: impl Into<Asset>, age: DisputeAge, disputes: usize, held: Decimal) -> Self {
+        Self {
+            asset: asset.into(),
+            age,
+            disputes,
+            held,
+        }
+    }
+}
+
+/// Record of an open escrow.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct EscrowRecord {
+    tx: TransactionId,
+    payer: ClientId,
+    payee: ClientId,
+    asset: Asset,
+    amount: Decimal,
+    timestamp: Timestamp,
+}
+
+impl EscrowRecord {
+    /// The transaction opening the escrow.
+    pub fn tx(&self) -> &TransactionId {
+        &self.tx
+    }
+
+    /// The client paying.
+    pub fn payer(&self) -> &ClientId {
+        &self.payer
+    }
+
+    /// The client paid once the escrow is released.
+    pub fn payee(&self) -> &ClientId {
+        &self.payee
+    }
+
+    /// The amount in escrow.
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// Record with the information of a disputable transaction.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct TransactionReport {
+    tx: TransactionId,
+    client: ClientId,
+    asset: Asset,
+    amount: Decimal,
+    state: DisputeState,
+    timestamp: Timestamp,
+    disputed: Decimal,
+    resolved: Decimal,
+    charged_back: Decimal,
+    reversed: Decimal,
+    refunded: Decimal,
+}
+
+impl TransactionReport {
+    fn from_disputable(tx: TransactionId, disputable_tx: &DisputableTransaction) -> Self {
+        let deposit = &disputable_tx.deposit;
+        Self::new(
+            tx,
+            deposit.client.clone(),
+            deposit.asset.clone(),
+            deposit.amount,
+            disputable_tx.state,
+            disputable_tx.timestamp,
+        )
+        .with_amounts(
+            disputable_tx.disputed,
+            disputable_tx.resolved,
+            disputable_tx.charged_back,
+        )
+        .with_reversed(disputable_tx.reversed)
+        .with_refunded(disputable_tx.refunded)
+    }
+
+    /// Creates a new transaction report, whose state applies to the whole amount.
+    pub fn new(
+        tx: impl Into<TransactionId>,
+        client: impl Into<ClientId>,
+        asset: impl Into<Asset>,
+        amount: Decimal,
+        state: DisputeState,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            tx: tx.into(),
+            client: client.into(),
+            asset: asset.into(),
+            amount,
+            state,
+            timestamp,
+            disputed: match state {
+                DisputeState::Disputed => amount,
+                _ => Decimal::ZERO,
+            },
+            resolved: Decimal::ZERO,
+            charged_back: match state {
+                DisputeState::Chargedback => amount,
+                _ => Decimal::ZERO,
+            },
+            reversed: Decimal::ZERO,
+            refunded: Decimal::ZERO,
+        }
+    }
+
+    /// Sets the amounts currently disputed, resolved so far and charged back so far.
+    pub fn with_amounts(
+        mut self,
+        disputed: Decimal,
+        resolved: Decimal,
+        charged_back: Decimal,
+    ) -> Self {
+        self.disputed = disputed;
+        self.resolved = resolved;
+        self.charged_back = charged_back;
+        self
+    }
+
+    /// Sets the amount of the chargebacks reversed so far.
+    pub fn with_reversed(mut self, reversed: Decimal) -> Self {
+        self.reversed = reversed;
+        self
+    }
+
+    /// Sets the amount refunded so far.
+    pub fn with_refunded(mut self, refunded: Decimal) -> Self {
+        self.refunded = refunded;
+        self
     }
+}
+
+/// Record with the client information converted into a base asset.
+#[derive(Serialize, Debug, Eq, PartialEq)]
+pub struct ConsolidatedRecord {
+    client: ClientId,
+    base: Asset,
+    available: Decimal,
+    held: Decimal,
+    total: Decimal,
+    locked: bool,
+    state: AccountState,
+}
 
-    /// Creates a new client record.
-    pub fn new(client: u16, available: Decimal, held: Decimal, locked: bool) -> Self {
+impl ConsolidatedRecord {
+    /// Creates a new consolidated record.
+    pub fn new(
+        client: impl Into<ClientId>,
+        base: impl Into<Asset>,
+        available: Decimal,
+        held: Decimal,
+        locked: bool,
+    ) -> Self {
         Self {
-            client: ClientId(client),
+            client: client.into(),
+            base: base.into(),
             available,
             held,
             total: available + held,
-            locked,
+            locked: false,
+            state: AccountState::default(),
         }
+        .with_state(AccountState::from_locked(locked))
     }
+
+    /// Sets the state of the consolidated account.
+    pub fn with_state(mut self, state: AccountState) -> Self {
+        self.locked = state.is_locked();
+        self.state = state;
+        self
+    }
+}
+
+/// Clients converted into a base asset, with the rates used for the conversion.
+#[derive(Debug)]
+pub struct Consolidation {
+    /// One record per client, ordered by client id.
+    pub records: Vec<ConsolidatedRecord>,
+    /// The rates used, ordered by currency pair.
+    pub rates: Vec<Rate>,
 }
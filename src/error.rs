@@ -2,6 +2,15 @@
 
 use std::io;
 
+use crate::{
+    engine::ClientId,
+    fx::Pair,
+    invariant::Violation,
+    tier::Tier,
+    transaction::{Operation, TransactionId},
+    Asset,
+};
+
 /// Structure for representing errors.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -11,6 +20,43 @@ pub enum Error {
     /// CSV error.
     #[error("CSV error")]
     CSVError(#[from] csv::Error),
+    /// A bookkeeping invariant was found broken.
+    #[error(
+        "invariant violated{}: {violation}",
+        tx.as_ref().map(|tx| format!(" after transaction {tx}")).unwrap_or_default()
+    )]
+    InvariantViolation {
+        /// The first transaction after which the invariant was broken, if known.
+        tx: Option<TransactionId>,
+        /// The broken invariant.
+        violation: Violation,
+    },
+    /// No exchange rate converts between two assets.
+    #[error("no exchange rate from {from:?} to {to:?}")]
+    MissingRate {
+        /// Asset to convert from.
+        from: Asset,
+        /// Asset to convert to.
+        to: Asset,
+    },
+    /// An exchange rate is zero or negative.
+    #[error("invalid exchange rate for {0}")]
+    InvalidRate(Pair),
+    /// A credit limit is negative.
+    #[error("invalid credit limit for client {0}")]
+    InvalidCreditLimit(ClientId),
+    /// A fee is negative, its minimum exceeds its maximum, or its operation is never charged.
+    #[error("invalid fee for {0:?}")]
+    InvalidFee(Operation),
+    /// An interest rate is negative.
+    #[error("invalid interest rate for tier {0:?}")]
+    InvalidInterestRate(Option<Tier>),
+    /// A limit row names both a client and a tier, or neither.
+    #[error("limit rows must name either a client or a tier")]
+    InvalidLimitRow,
+    /// A numeric id is not a `u64`.
+    #[error("invalid numeric id {0:?}")]
+    InvalidId(String),
     /// An unknown error.
     #[error("unknown error")]
     Unknown,
@@ -0,0 +1,94 @@
+//! Fees charged on operations, credited to the house revenue account.
+
+use std::ops::Not;
+
+use csv::Reader;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{asset::AssetTable, transaction::Operation, Asset, Error};
+
+/// Number of decimal places of fees.
+pub const FEE_SCALE: u32 = 4;
+
+/// Fee of an operation: a flat part plus a percentage of the amount, kept between a minimum and
+/// a maximum.
+///
+/// Deposits, withdrawals, transfers, refunds, authorizations and escrows pay their fee along
+/// with the transaction, which is refused if the client can't afford both. Chargebacks charge
+/// their fee as a penalty on the charged back client, whatever its balance.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Fee {
+    /// The operation charged.
+    pub operation: Operation,
+    /// The asset of the transactions charged, the default asset if `None`.
+    pub asset: Option<Asset>,
+    /// Flat part of the fee.
+    pub flat: Option<Decimal>,
+    /// Percentage of the amount of the transaction.
+    pub percent: Option<Decimal>,
+    /// Minimum fee.
+    pub min: Option<Decimal>,
+    /// Maximum fee.
+    pub max: Option<Decimal>,
+}
+
+impl Fee {
+    /// Returns the fee of a transaction of `amount`, rounded half to even at [`FEE_SCALE`]
+    /// places.
+    pub fn amount(&self, amount: Decimal) -> Decimal {
+        let mut fee = self.flat.unwrap_or_default()
+            + amount * self.percent.unwrap_or_default() / Decimal::ONE_HUNDRED;
+        if let Some(min) = self.min {
+            fee = fee.max(min);
+        }
+        if let Some(max) = self.max {
+            fee = fee.min(max);
+        }
+        fee.round_dp_with_strategy(FEE_SCALE, RoundingStrategy::MidpointNearestEven)
+    }
+
+    fn is_valid(&self) -> bool {
+        // Only the operations moving funds, and chargebacks as a penalty, are charged.
+        let charged = self.operation.moves_funds() || self.operation == Operation::Chargeback;
+        let parts = [self.flat, self.percent, self.min, self.max];
+        charged
+            && parts.iter().flatten().all(|part| *part >= Decimal::ZERO)
+            && self.min.zip(self.max).is_none_or(|(min, max)| min <= max)
+    }
+}
+
+/// Fee schedule per operation and asset, operations without a fee being free.
+#[derive(Default, Debug)]
+pub struct FeeSchedule {
+    fees: AssetTable<Operation, Fee>,
+}
+
+impl FeeSchedule {
+    /// Loads a fee schedule from a `csv::Reader` with the `operation,asset,flat,percent,min,max`
+    /// columns, refusing fees on operations that are never charged.
+    pub fn load_from_reader<R: std::io::Read>(mut reader: Reader<R>) -> Result<Self, Error> {
+        let mut fees = AssetTable::default();
+        for result in reader.deserialize() {
+            let fee: Fee = result?;
+            if fee.is_valid().not() {
+                return Err(Error::InvalidFee(fee.operation));
+            }
+            fees.insert(fee.operation, fee.asset.clone(), fee);
+        }
+        Ok(Self { fees })
+    }
+
+    /// Returns the fee of an operation on `amount` of an asset, zero if it's free.
+    pub fn fee(
+        &self,
+        operation: Operation,
+        asset: &Asset,
+        amount: Decimal,
+        default_asset: &Asset,
+    ) -> Decimal {
+        self.fees
+            .get(&operation, asset, default_asset)
+            .map_or(Decimal::ZERO, |fee| fee.amount(amount))
+    }
+}
@@ -0,0 +1,137 @@
+//! Currency conversion for consolidated reporting.
+
+use std::{fmt, str::FromStr};
+
+use csv::Reader;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use crate::{Asset, Error, Timestamp};
+
+/// Number of decimal places of converted amounts.
+pub const CONVERSION_SCALE: u32 = 4;
+
+/// Rounds a converted amount: half to even (banker's rounding) at [`CONVERSION_SCALE`] places.
+pub fn round(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(CONVERSION_SCALE, RoundingStrategy::MidpointNearestEven)
+}
+
+/// A currency pair written `BASE/QUOTE`, whose rate is the price of one `BASE` in `QUOTE`.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct Pair {
+    /// The asset being priced.
+    pub base: Asset,
+    /// The asset the price is expressed in.
+    pub quote: Asset,
+}
+
+impl FromStr for Pair {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() => Ok(Self {
+                base: base.into(),
+                quote: quote.into(),
+            }),
+            _ => Err(format!("invalid currency pair `{s}`, expected BASE/QUOTE")),
+        }
+    }
+}
+
+impl fmt::Display for Pair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pair {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for Pair {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// One row of the rate table.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Rate {
+    /// The currency pair.
+    pub pair: Pair,
+    /// Price of one base unit in the quote asset.
+    pub rate: Decimal,
+    /// Time from which the rate applies.
+    pub effective: Timestamp,
+}
+
+/// Table of exchange rates, each effective from a point in time.
+#[derive(Default, Debug)]
+pub struct RateTable {
+    rates: Vec<Rate>,
+}
+
+impl RateTable {
+    /// Loads a rate table from a `csv::Reader` with the `pair,rate,effective` columns.
+    pub fn load_from_reader<R: std::io::Read>(mut reader: Reader<R>) -> Result<Self, Error> {
+        let mut rates = Vec::new();
+        for result in reader.deserialize() {
+            let rate: Rate = result?;
+            if rate.rate <= Decimal::ZERO {
+                return Err(Error::InvalidRate(rate.pair));
+            }
+            rates.push(rate);
+        }
+        Ok(Self { rates })
+    }
+
+    /// Returns the rate converting `from` into `to`, effective at `at` (or the latest if `None`).
+    ///
+    /// Rates quoted the other way around (`to/from`) are inverted. Converting an asset into
+    /// itself uses no rate and returns `None`.
+    pub fn lookup(
+        &self,
+        from: &Asset,
+        to: &Asset,
+        at: Option<Timestamp>,
+    ) -> Result<Option<&Rate>, Error> {
+        if from == to {
+            return Ok(None);
+        }
+        self.rates
+            .iter()
+            .filter(|rate| {
+                (rate.pair.base == *from && rate.pair.quote == *to)
+                    || (rate.pair.base == *to && rate.pair.quote == *from)
+            })
+            .filter(|rate| at.is_none_or(|at| rate.effective <= at))
+            .max_by_key(|rate| rate.effective)
+            .map(Some)
+            .ok_or_else(|| Error::MissingRate {
+                from: from.clone(),
+                to: to.clone(),
+            })
+    }
+
+    /// Converts `amount` of `from` into `to`, without rounding, returning the rate used.
+    pub fn convert<'a>(
+        &'a self,
+        amount: Decimal,
+        from: &Asset,
+        to: &Asset,
+        at: Option<Timestamp>,
+    ) -> Result<(Decimal, Option<&'a Rate>), Error> {
+        let rate = self.lookup(from, to, at)?;
+        let converted = match rate {
+            None => amount,
+            Some(rate) if rate.pair.base == *from => amount * rate.rate,
+            Some(rate) => amount / rate.rate,
+        };
+        Ok((converted, rate))
+    }
+}
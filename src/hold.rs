@@ -0,0 +1,33 @@
+//! Funds held on client accounts.
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{transaction::TransactionId, Asset, Wallet};
+
+/// Why funds are held.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldReason {
+    /// The deposit is under dispute.
+    Dispute,
+    /// The funds are authorized, waiting for a capture or void.
+    Authorization,
+    /// The funds are in escrow, waiting to be released to the payee or returned.
+    Escrow,
+}
+
+/// Funds held for one reason by one transaction.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Hold {
+    /// The wallet holding the funds.
+    pub wallet: Wallet,
+    /// The asset of the held funds.
+    pub asset: Asset,
+    /// Why the funds are held.
+    pub reason: HoldReason,
+    /// The transaction the funds are held for.
+    pub tx: TransactionId,
+    /// The amount held.
+    pub amount: Decimal,
+}
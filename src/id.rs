@@ -0,0 +1,108 @@
+//! Identifiers shared by clients and transactions.
+
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Error;
+
+/// How the ids of an input are read, the same for all its clients and transactions.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IdKind {
+    /// Ids are `u64` numbers, so `02` and `2` are the same id.
+    #[default]
+    Numeric,
+    /// Ids are strings (e.g. UUIDs or upstream references), kept verbatim.
+    Text,
+}
+
+/// An identifier, either numeric or textual (e.g. a UUID or an upstream reference).
+///
+/// Fields are read verbatim, then converted into the [`IdKind`] chosen for the input.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Identifier {
+    /// A numeric id up to `u64::MAX`.
+    Numeric(u64),
+    /// Any other id.
+    Text(Box<str>),
+}
+
+impl From<u64> for Identifier {
+    fn from(value: u64) -> Self {
+        Self::Numeric(value)
+    }
+}
+
+impl From<&str> for Identifier {
+    fn from(value: &str) -> Self {
+        Self::Text(value.into())
+    }
+}
+
+impl Identifier {
+    /// Reads an id of the given kind.
+    pub fn parse(value: &str, kind: IdKind) -> Result<Self, Error> {
+        match kind {
+            IdKind::Numeric => value
+                .parse()
+                .map(Self::Numeric)
+                .map_err(|_| Error::InvalidId(value.into())),
+            IdKind::Text => Ok(Self::Text(value.into())),
+        }
+    }
+
+    /// Converts an id read verbatim into the given kind.
+    pub(crate) fn into_kind(self, kind: IdKind) -> Result<Self, Error> {
+        match self {
+            Self::Text(value) => Self::parse(&value, kind),
+            numeric => Ok(numeric),
+        }
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numeric(n) => n.fmt(f),
+            Self::Text(s) => s.fmt(f),
+        }
+    }
+}
+
+impl Serialize for Identifier {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Numeric(n) => serializer.serialize_u64(*n),
+            Self::Text(s) => serializer.serialize_str(s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl de::Visitor<'_> for Visitor {
+            type Value = Identifier;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a numeric or textual identifier")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Identifier::Numeric(v))
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                if v.is_empty() {
+                    return Err(E::invalid_length(0, &self));
+                }
+                Ok(v.into())
+            }
+        }
+
+        // Ask for the raw string, otherwise self-describing formats would turn `007` into 7.
+        deserializer.deserialize_str(Visitor)
+    }
+}
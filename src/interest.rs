@@ -0,0 +1,124 @@
+//! Interest accrued on the available balances of clients.
+
+use csv::Reader;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{asset::AssetTable, tier::Tier, Asset, Error, Timestamp};
+
+/// Number of decimal places of interest postings.
+pub const INTEREST_SCALE: u32 = 4;
+
+/// How the fraction of a year between two dates is counted.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DayCount {
+    /// Actual number of days over a 360 days year.
+    #[serde(rename = "actual/360")]
+    Actual360,
+    /// Actual number of days over a 365 days year.
+    #[default]
+    #[serde(rename = "actual/365")]
+    Actual365,
+    /// Months of 30 days over a 360 days year.
+    #[serde(rename = "30/360")]
+    Thirty360,
+}
+
+impl DayCount {
+    /// Returns the fraction of a year from `start` to `end`, counting whole days.
+    pub fn year_fraction(self, start: Timestamp, end: Timestamp) -> Decimal {
+        let days = match self {
+            Self::Actual360 | Self::Actual365 => {
+                Decimal::from(end.as_days().saturating_sub(start.as_days()))
+            }
+            Self::Thirty360 => {
+                let (start, end) = (start.date(), end.date());
+                let start_day = start.day.min(30);
+                let end_day = if start_day == 30 {
+                    end.day.min(30)
+                } else {
+                    end.day
+                };
+                let days = 360 * (end.year as i64 - start.year as i64)
+                    + 30 * (end.month as i64 - start.month as i64)
+                    + (end_day as i64 - start_day as i64);
+                Decimal::from(days.max(0))
+            }
+        };
+        let year = match self {
+            Self::Actual360 | Self::Thirty360 => 360,
+            Self::Actual365 => 365,
+        };
+        days / Decimal::from(year)
+    }
+}
+
+/// One row of the interest rate table.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct InterestRate {
+    /// The tier earning the rate, `None` for the clients without a rate of their tier.
+    pub tier: Option<Tier>,
+    /// The asset earning the rate, the default asset if `None`.
+    pub asset: Option<Asset>,
+    /// Yearly rate, in percent.
+    pub rate: Decimal,
+    /// Day-count convention, actual/365 if `None`.
+    pub convention: Option<DayCount>,
+}
+
+impl InterestRate {
+    /// Returns the interest earned by `balance` from `start` to `end`, unrounded so that the
+    /// interest of successive periods adds up.
+    pub fn interest(&self, balance: Decimal, start: Timestamp, end: Timestamp) -> Decimal {
+        let fraction = self
+            .convention
+            .unwrap_or_default()
+            .year_fraction(start, end);
+        balance * self.rate / Decimal::ONE_HUNDRED * fraction
+    }
+}
+
+/// Rounds accrued interest half to even at [`INTEREST_SCALE`] places, the amount posted.
+pub fn round_interest(interest: Decimal) -> Decimal {
+    interest.round_dp_with_strategy(INTEREST_SCALE, RoundingStrategy::MidpointNearestEven)
+}
+
+/// Table of the interest rates per tier and asset, balances without a rate earning nothing.
+#[derive(Default, Debug)]
+pub struct InterestRates {
+    rates: AssetTable<Option<Tier>, InterestRate>,
+}
+
+impl InterestRates {
+    /// Loads an interest rate table from a `csv::Reader` with the `tier,asset,rate,convention`
+    /// columns.
+    pub fn load_from_reader<R: std::io::Read>(mut reader: Reader<R>) -> Result<Self, Error> {
+        let mut rates = AssetTable::default();
+        for result in reader.deserialize() {
+            let rate: InterestRate = result?;
+            if rate.rate < Decimal::ZERO {
+                return Err(Error::InvalidInterestRate(rate.tier));
+            }
+            rates.insert(rate.tier.clone(), rate.asset.clone(), rate);
+        }
+        Ok(Self { rates })
+    }
+
+    /// Whether the table has no rate, no balance earning interest.
+    pub fn is_empty(&self) -> bool {
+        self.rates.is_empty()
+    }
+
+    /// Returns the rate of an asset for a client tier: the tier's own, or else the one of the
+    /// clients without a rate of their tier.
+    pub fn lookup(
+        &self,
+        tier: Option<&Tier>,
+        asset: &Asset,
+        default_asset: &Asset,
+    ) -> Option<&InterestRate> {
+        self.rates
+            .get(&tier.cloned(), asset, default_asset)
+            .or_else(|| self.rates.get(&None, asset, default_asset))
+    }
+}
@@ -0,0 +1,125 @@
+//! Checks of the engine bookkeeping invariants.
+
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+
+use std::collections::BTreeMap;
+
+use crate::{
+    engine::{ClientId, Engine},
+    Asset,
+};
+
+/// When the engine verifies its conservation invariants.
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvariantCheck {
+    /// Never check.
+    Off,
+    /// Check after every transaction, pinpointing the first violating one.
+    EachTransaction,
+    /// Check once, after the whole input has been loaded.
+    EndOfRun,
+}
+
+impl Default for InvariantCheck {
+    /// Debug builds check once at the end of the run, release builds don't check: checking after
+    /// every transaction rescans all the clients.
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            Self::EndOfRun
+        } else {
+            Self::Off
+        }
+    }
+}
+
+/// A broken invariant.
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
+pub enum Violation {
+    /// The client total differs from available plus held funds.
+    #[error("client {0}, asset {1:?}: total is not available + held")]
+    TotalMismatch(ClientId, Asset),
+    /// The client has negative held funds.
+    #[error("client {0}, asset {1:?}: held funds are negative")]
+    NegativeHeld(ClientId, Asset),
+    /// The client held funds differ from the sum of its holds.
+    #[error("client {0}, asset {1:?}: held funds are not the sum of the holds")]
+    HoldMismatch(ClientId, Asset),
+    /// The sum of all balances, suspense included, differs from deposits - withdrawals -
+    /// refunds - chargebacks + reversed chargebacks + adjustments + interest.
+    #[error("asset {asset:?}: sum of balances is {balances}, expected {expected}")]
+    Conservation {
+        /// The asset whose balances don't add up.
+        asset: Asset,
+        /// Sum of all client totals.
+        balances: Decimal,
+        /// Deposits minus withdrawals, refunds and chargebacks plus reversals, adjustments and
+        /// interest.
+        expected: Decimal,
+    },
+}
+
+/// Running totals of the money that entered and left the engine, for one asset.
+#[derive(Default, Clone, Copy, Debug)]
+pub(crate) struct Totals {
+    pub(crate) deposited: Decimal,
+    pub(crate) withdrawn: Decimal,
+    pub(crate) refunded: Decimal,
+    pub(crate) charged_back: Decimal,
+    pub(crate) reversed: Decimal,
+    pub(crate) adjusted: Decimal,
+    pub(crate) interest: Decimal,
+}
+
+/// Verifies all invariants, returning the first one found broken.
+pub(crate) fn check(engine: &Engine) -> Result<(), Violation> {
+    let mut sums: BTreeMap<&Asset, Decimal> = BTreeMap::new();
+    for (id, data) in engine.clients() {
+        for (wallet, asset, balance) in data.wallet_balances() {
+            if balance.held() < Decimal::ZERO {
+                return Err(Violation::NegativeHeld(id.clone(), asset.clone()));
+            }
+            if balance.total() != balance.available() + balance.held() {
+                return Err(Violation::TotalMismatch(id.clone(), asset.clone()));
+            }
+            let holds: Decimal = data
+                .holds()
+                .iter()
+                .filter(|hold| hold.wallet == *wallet && hold.asset == *asset)
+                .map(|hold| hold.amount)
+                .sum();
+            if balance.held() != holds {
+                return Err(Violation::HoldMismatch(id.clone(), asset.clone()));
+            }
+            *sums.entry(asset).or_default() += balance.total();
+        }
+    }
+    // Money in the suspense account was deposited too.
+    for suspended in engine.suspense().values().filter(|s| !s.released) {
+        *sums.entry(&suspended.deposit.asset).or_default() += suspended.deposit.amount;
+    }
+
+    for (asset, totals) in engine.totals() {
+        let balances = sums.remove(asset).unwrap_or_default();
+        let expected = totals.deposited - totals.withdrawn - totals.refunded - totals.charged_back
+            + totals.reversed
+            + totals.adjusted
+            + totals.interest;
+        if balances != expected {
+            return Err(Violation::Conservation {
+                asset: asset.clone(),
+                balances,
+                expected,
+            });
+        }
+    }
+    // Balances in an asset that never had any deposit.
+    if let Some((asset, balances)) = sums.into_iter().find(|(_, sum)| !sum.is_zero()) {
+        return Err(Violation::Conservation {
+            asset: asset.clone(),
+            balances,
+            expected: Decimal::ZERO,
+        });
+    }
+    Ok(())
+}
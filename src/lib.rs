@@ -3,13 +3,55 @@
 
 //! Toy transaction engine
 
+pub mod asset;
+pub use asset::Asset;
+
+pub mod clock;
+pub use clock::{Clock, Timestamp};
+
 pub mod config;
 pub use config::Config;
 
+pub mod credit;
+pub use credit::CreditLimits;
+
 pub mod engine;
 pub use engine::Engine;
 
 pub mod error;
 pub use error::Error;
 
+pub mod fee;
+pub use fee::FeeSchedule;
+
+pub mod fx;
+pub use fx::RateTable;
+
+pub mod hold;
+pub use hold::HoldReason;
+
+pub mod id;
+pub use id::{IdKind, Identifier};
+
+pub mod interest;
+pub use interest::InterestRates;
+
+pub mod invariant;
+pub use invariant::InvariantCheck;
+
+pub mod lifecycle;
+pub use lifecycle::AccountState;
+
+pub mod limits;
+pub use limits::LimitTable;
+
+pub mod policy;
+pub use policy::{DisputePolicy, LockPolicy};
+
+pub mod tier;
+pub use tier::{ClientTiers, Tier};
+
 pub mod transaction;
+
+pub mod wallet;
+pub use wallet::Wallet;
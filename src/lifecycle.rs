@@ -0,0 +1,80 @@
+//! Lifecycle of client accounts.
+
+use serde::Serialize;
+
+use crate::{transaction::TransactionId, Timestamp};
+
+/// State of a client account.
+#[derive(Serialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountState {
+    /// Fully operational.
+    #[default]
+    Active,
+    /// Withdrawals are refused.
+    Restricted,
+    /// Locked, refusing what the lock policy says.
+    Frozen,
+    /// Inactive for too long, withdrawals are refused until reactivated.
+    Dormant,
+    /// Closed, refusing everything.
+    Closed,
+}
+
+impl AccountState {
+    /// The state reported by records built from a `locked` flag.
+    pub(crate) fn from_locked(locked: bool) -> Self {
+        if locked {
+            Self::Frozen
+        } else {
+            Self::Active
+        }
+    }
+
+    /// Whether the account is reported as locked, which only frozen accounts are.
+    pub fn is_locked(self) -> bool {
+        self == Self::Frozen
+    }
+
+    /// Whether an account can move from this state into `to`.
+    ///
+    /// Closed accounts never change, only active accounts become dormant and frozen accounts
+    /// must be unfrozen before being closed.
+    pub fn can_become(self, to: Self) -> bool {
+        match (self, to) {
+            (from, to) if from == to => false,
+            (Self::Closed, _) => false,
+            (from, Self::Dormant) => from == Self::Active,
+            (Self::Frozen, Self::Closed) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Why an account changed state.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub enum StateReason {
+    /// The account reached the number of chargebacks of the lock policy.
+    Chargebacks(u32),
+    /// An operator changed the state, with their reason.
+    Operator(String),
+    /// The account had no transaction for the dormancy period.
+    Inactivity,
+    /// A deposit reactivated a dormant account.
+    Activity,
+    /// Chargebacks were reversed, bringing the account back under the lock policy threshold.
+    ChargebackReversal,
+}
+
+/// Change of the state of an account.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct StateEvent {
+    /// The new state.
+    pub state: AccountState,
+    /// Why the state changed.
+    pub reason: StateReason,
+    /// The transaction that changed the state, if any.
+    pub tx: Option<TransactionId>,
+    /// When the state changed.
+    pub timestamp: Timestamp,
+}
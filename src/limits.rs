@@ -0,0 +1,209 @@
+//! Per-client transaction limits, and the running counters they are checked against.
+
+use csv::Reader;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{asset::AssetTable, engine::ClientId, tier::Tier, Asset, Error, IdKind, Timestamp};
+
+/// Limits on the funds movements of a client in one asset, `None` meaning no limit.
+///
+/// Deposits and debits (withdrawals, transfers, refunds, authorizations and escrows) are
+/// limited in amount and count, debits also in their daily and monthly totals.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Limits {
+    /// Maximum amount of a single transaction.
+    pub max_amount: Option<Decimal>,
+    /// Maximum amount debited per calendar day.
+    pub daily_withdrawals: Option<Decimal>,
+    /// Maximum amount debited per calendar month.
+    pub monthly_withdrawals: Option<Decimal>,
+    /// Maximum number of transactions per calendar day.
+    pub daily_transactions: Option<u32>,
+    /// Maximum number of transactions per calendar month.
+    pub monthly_transactions: Option<u32>,
+}
+
+/// One row of the limit table, for either a client or a tier.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct LimitRow {
+    /// The client the limits apply to.
+    pub client: Option<ClientId>,
+    /// The tier the limits apply to, for its clients without limits of their own.
+    pub tier: Option<Tier>,
+    /// The asset the limits apply to, the default asset if `None`.
+    pub asset: Option<Asset>,
+    /// See [`Limits::max_amount`].
+    pub max_amount: Option<Decimal>,
+    /// See [`Limits::daily_withdrawals`].
+    pub daily_withdrawals: Option<Decimal>,
+    /// See [`Limits::monthly_withdrawals`].
+    pub monthly_withdrawals: Option<Decimal>,
+    /// See [`Limits::daily_transactions`].
+    pub daily_transactions: Option<u32>,
+    /// See [`Limits::monthly_transactions`].
+    pub monthly_transactions: Option<u32>,
+}
+
+/// Table of the limits per client or tier and asset, clients without any being unlimited.
+#[derive(Default, Debug)]
+pub struct LimitTable {
+    clients: AssetTable<ClientId, Limits>,
+    tiers: AssetTable<Tier, Limits>,
+}
+
+impl LimitTable {
+    /// Loads a limit table from a `csv::Reader` with the `client,tier,asset,max_amount,
+    /// daily_withdrawals,monthly_withdrawals,daily_transactions,monthly_transactions` columns,
+    /// reading the client ids as the given kind.
+    pub fn load_from_reader<R: std::io::Read>(
+        mut reader: Reader<R>,
+        kind: IdKind,
+    ) -> Result<Self, Error> {
+        let mut table = Self::default();
+        for result in reader.deserialize() {
+            let row: LimitRow = result?;
+            let limits = Limits {
+                max_amount: row.max_amount,
+                daily_withdrawals: row.daily_withdrawals,
+                monthly_withdrawals: row.monthly_withdrawals,
+                daily_transactions: row.daily_transactions,
+                monthly_transactions: row.monthly_transactions,
+            };
+            match (row.client, row.tier) {
+                (Some(client), None) => {
+                    let client = client.into_kind(kind)?;
+                    table.clients.insert(client, row.asset, limits)
+                }
+                (None, Some(tier)) => table.tiers.insert(tier, row.asset, limits),
+                _ => return Err(Error::InvalidLimitRow),
+            };
+        }
+        Ok(table)
+    }
+
+    /// Returns the limits of a client in an asset: its own, or else those of its tier.
+    pub fn lookup(
+        &self,
+        client: &ClientId,
+        tier: Option<&Tier>,
+        asset: &Asset,
+        default_asset: &Asset,
+    ) -> Option<&Limits> {
+        self.clients
+            .get(client, asset, default_asset)
+            .or_else(|| self.tiers.get(tier?, asset, default_asset))
+    }
+}
+
+/// Why a transaction was refused by the limits of its client.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitBreach {
+    /// The amount exceeds the maximum of a single transaction.
+    MaxAmount,
+    /// The debits of the day would exceed their maximum.
+    DailyWithdrawals,
+    /// The debits of the month would exceed their maximum.
+    MonthlyWithdrawals,
+    /// The client already made the maximum number of transactions of the day.
+    DailyTransactions,
+    /// The client already made the maximum number of transactions of the month.
+    MonthlyTransactions,
+}
+
+/// Running counters of the funds movements of a client in one asset, for the current day and
+/// month.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Usage {
+    day: u64,
+    month: (u64, u32),
+    daily_withdrawn: Decimal,
+    monthly_withdrawn: Decimal,
+    daily_transactions: u32,
+    monthly_transactions: u32,
+}
+
+impl Usage {
+    /// Amount debited on the day of the last transaction.
+    pub fn daily_withdrawn(&self) -> Decimal {
+        self.daily_withdrawn
+    }
+
+    /// Amount debited in the month of the last transaction.
+    pub fn monthly_withdrawn(&self) -> Decimal {
+        self.monthly_withdrawn
+    }
+
+    /// Number of transactions on the day of the last transaction.
+    pub fn daily_transactions(&self) -> u32 {
+        self.daily_transactions
+    }
+
+    /// Number of transactions in the month of the last transaction.
+    pub fn monthly_transactions(&self) -> u32 {
+        self.monthly_transactions
+    }
+
+    /// Returns the counters as of `at`, starting over on a new day or month.
+    fn at(&self, at: Timestamp) -> Self {
+        let mut usage = self.clone();
+        if at.as_days() != usage.day {
+            usage.day = at.as_days();
+            usage.daily_withdrawn = Decimal::ZERO;
+            usage.daily_transactions = 0;
+        }
+        let date = at.date();
+        if (date.year, date.month) != usage.month {
+            usage.month = (date.year, date.month);
+            usage.monthly_withdrawn = Decimal::ZERO;
+            usage.monthly_transactions = 0;
+        }
+        usage
+    }
+
+    /// Checks a transaction of `amount` at `at` against the limits.
+    pub(crate) fn check(
+        &self,
+        limits: &Limits,
+        debit: bool,
+        amount: Decimal,
+        at: Timestamp,
+    ) -> Result<(), LimitBreach> {
+        let usage = self.at(at);
+        let exceeds = |limit: Option<Decimal>, value: Decimal| limit.is_some_and(|l| value > l);
+        if exceeds(limits.max_amount, amount) {
+            return Err(LimitBreach::MaxAmount);
+        }
+        if debit && exceeds(limits.daily_withdrawals, usage.daily_withdrawn + amount) {
+            return Err(LimitBreach::DailyWithdrawals);
+        }
+        if debit && exceeds(limits.monthly_withdrawals, usage.monthly_withdrawn + amount) {
+            return Err(LimitBreach::MonthlyWithdrawals);
+        }
+        if limits
+            .daily_transactions
+            .is_some_and(|l| usage.daily_transactions >= l)
+        {
+            return Err(LimitBreach::DailyTransactions);
+        }
+        if limits
+            .monthly_transactions
+            .is_some_and(|l| usage.monthly_transactions >= l)
+        {
+            return Err(LimitBreach::MonthlyTransactions);
+        }
+        Ok(())
+    }
+
+    /// Counts a transaction of `amount` applied at `at`.
+    pub(crate) fn record(&mut self, debit: bool, amount: Decimal, at: Timestamp) {
+        *self = self.at(at);
+        if debit {
+            self.daily_withdrawn += amount;
+            self.monthly_withdrawn += amount;
+        }
+        self.daily_transactions += 1;
+        self.monthly_transactions += 1;
+    }
+}
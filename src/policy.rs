@@ -0,0 +1,61 @@
+//! Policies tuning how the engine treats accounts.
+
+use clap::ValueEnum;
+
+/// What freezing (locking) an account blocks, and when accounts get locked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LockPolicy {
+    /// Whether locked accounts refuse deposits.
+    pub blocks_deposits: bool,
+    /// Whether locked accounts refuse withdrawals.
+    pub blocks_withdrawals: bool,
+    /// Whether locked accounts refuse new disputes.
+    pub blocks_disputes: bool,
+    /// Number of chargebacks locking the account, `0` to never lock on chargebacks.
+    pub chargebacks_to_lock: u32,
+    /// Whether disputes opened before the lock can still be resolved or charged back.
+    pub resolves_pending_disputes: bool,
+    /// Whether reversing chargebacks unlocks accounts locked by chargebacks, once they're back
+    /// under the number of chargebacks locking them.
+    pub reversal_unlocks: bool,
+}
+
+impl Default for LockPolicy {
+    /// Locks on the first chargeback, blocking deposits and withdrawals only.
+    fn default() -> Self {
+        Self {
+            blocks_deposits: true,
+            blocks_withdrawals: true,
+            blocks_disputes: false,
+            chargebacks_to_lock: 1,
+            resolves_pending_disputes: true,
+            reversal_unlocks: false,
+        }
+    }
+}
+
+/// How many times transactions can be disputed, and how long disputes can stay open.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DisputePolicy {
+    /// Number of dispute cycles after which disputes are refused, `0` for no limit.
+    ///
+    /// A cycle opens when an undisputed transaction gets disputed; further partial disputes
+    /// while it's still under dispute belong to the same cycle.
+    pub max_cycles: u32,
+    /// Seconds after which open disputes expire.
+    pub expire_after: Option<u64>,
+    /// Number of subsequent transactions after which open disputes expire.
+    pub expire_after_transactions: Option<u64>,
+    /// What happens to expired disputes.
+    pub on_expiry: ExpiryAction,
+}
+
+/// What happens to the funds of an expired dispute.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ExpiryAction {
+    /// The dispute is resolved, releasing the held funds to the client.
+    #[default]
+    Resolve,
+    /// The dispute is charged back, as if the client had lost it.
+    Chargeback,
+}
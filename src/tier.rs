@@ -0,0 +1,61 @@
+//! Tiers (product levels) of clients, sharing the same configuration.
+
+use std::fmt;
+
+use csv::Reader;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{engine::ClientId, Error, IdKind};
+
+/// Name of a client tier, e.g. `retail` or `premium`.
+#[derive(Deserialize, Serialize, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Tier(Box<str>);
+
+impl From<&str> for Tier {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// One row of the client tier table.
+#[derive(Deserialize, Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ClientTier {
+    /// The client.
+    pub client: ClientId,
+    /// The tier of the client.
+    pub tier: Tier,
+}
+
+/// Table of the client tiers, clients without one having no tier.
+#[derive(Default, Debug)]
+pub struct ClientTiers {
+    tiers: FxHashMap<ClientId, Tier>,
+}
+
+impl ClientTiers {
+    /// Loads a client tier table from a `csv::Reader` with the `client,tier` columns, reading
+    /// the client ids as the given kind.
+    pub fn load_from_reader<R: std::io::Read>(
+        mut reader: Reader<R>,
+        kind: IdKind,
+    ) -> Result<Self, Error> {
+        let mut tiers = FxHashMap::default();
+        for result in reader.deserialize() {
+            let row: ClientTier = result?;
+            tiers.insert(row.client.into_kind(kind)?, row.tier);
+        }
+        Ok(Self { tiers })
+    }
+
+    /// Returns the tier of a client, if any.
+    pub fn tier(&self, client: &ClientId) -> Option<&Tier> {
+        self.tiers.get(client)
+    }
+}
@@ -1,22 +1,118 @@
 //! Object definitions for transactions.
 
+use std::fmt;
+
 use derive_more::Constructor;
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::engine::ClientId;
+use crate::{engine::ClientId, Asset, Error, IdKind, Identifier, Timestamp, Wallet};
 
-#[derive(Deserialize, Clone, Copy, Hash, Eq, PartialEq)]
-pub(crate) struct TransactionId(u32);
+/// Id of a transaction.
+#[derive(Deserialize, Serialize, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct TransactionId(Identifier);
 
-#[derive(Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub(crate) enum Operation {
+impl<T: Into<Identifier>> From<T> for TransactionId {
+    fn from(value: T) -> Self {
+        Self(value.into())
+    }
+}
+
+impl TransactionId {
+    pub(crate) fn into_kind(self, kind: IdKind) -> Result<Self, Error> {
+        self.0.into_kind(kind).map(Self)
+    }
+}
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Type of a transaction record.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Hash, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// Credit to the client.
     Deposit,
+    /// Debit from the client.
     Withdrawal,
+    /// Move of funds from the client to another one.
+    Transfer,
+    /// Return of all or part of a deposit to whoever made it.
+    Refund,
+    /// Claim that a deposit was erroneous.
     Dispute,
+    /// Dispute closed in favour of the client.
     Resolve,
+    /// Dispute closed against the client.
     Chargeback,
+    /// Chargeback reversed after a successful representment.
+    ChargebackReversal,
+    /// Hold of available funds for a later capture.
+    Authorize,
+    /// Settlement of an authorization, withdrawing the held funds.
+    Capture,
+    /// Cancellation of an authorization, releasing the held funds.
+    Void,
+    /// Hold of available funds of a payer, to be paid to a payee.
+    EscrowOpen,
+    /// Payment of escrowed funds to the payee.
+    EscrowRelease,
+    /// Return of escrowed funds to the payer.
+    EscrowCancel,
+    /// Administrative lock of the account.
+    Lock,
+    /// Administrative unlock of the account.
+    Unlock,
+    /// Administrative credit (positive amount) or debit (negative amount).
+    Adjust,
+    /// Administrative restriction of the account, refusing withdrawals.
+    Restrict,
+    /// Administrative closure of the account, paying out the remaining balance.
+    Close,
+    /// Administrative release of a suspended deposit to its client.
+    Release,
+    /// Administrative undoing of a past deposit or withdrawal.
+    Reversal,
+    /// Administrative merge of a duplicate account into another one, closing it.
+    Merge,
+    /// Administrative interest run, crediting the interest accrued since the previous one.
+    Accrue,
+}
+
+impl Operation {
+    /// Whether the operation moves funds in or out of the client's account, counting towards
+    /// its limits.
+    pub fn moves_funds(&self) -> bool {
+        *self == Self::Deposit || self.debits()
+    }
+
+    /// Whether the operation is reserved to operators.
+    pub fn is_admin(&self) -> bool {
+        matches!(
+            self,
+            Self::Lock
+                | Self::Unlock
+                | Self::Adjust
+                | Self::Restrict
+                | Self::Close
+                | Self::Release
+                | Self::Reversal
+                | Self::Merge
+                | Self::Accrue
+        )
+    }
+
+    /// Whether the operation takes available funds out of the client, being refused wherever
+    /// withdrawals are.
+    pub fn debits(&self) -> bool {
+        matches!(
+            self,
+            Self::Withdrawal | Self::Authorize | Self::Transfer | Self::Refund | Self::EscrowOpen
+        )
+    }
 }
 
 /// Struct representation of a transaction record from the input file.
@@ -26,17 +122,74 @@ pub(crate) struct TransactionRecord {
     pub(crate) client: ClientId,
     pub(crate) tx: TransactionId,
     pub(crate) amount: Option<Decimal>,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) timestamp: Option<Timestamp>,
+    pub(crate) wallet: Option<Wallet>,
+    pub(crate) to: Option<ClientId>,
+    pub(crate) to_wallet: Option<Wallet>,
+    pub(crate) operator: Option<String>,
+    pub(crate) reason: Option<String>,
+}
+
+impl TransactionRecord {
+    /// Converts the client and transaction ids into the given kind.
+    pub(crate) fn into_id_kind(mut self, kind: IdKind) -> Result<Self, Error> {
+        // Accruals apply to all clients, so their client is a placeholder.
+        if self.r#type != Operation::Accrue {
+            self.client = self.client.into_kind(kind)?;
+        }
+        self.tx = self.tx.into_kind(kind)?;
+        self.to = self.to.map(|to| to.into_kind(kind)).transpose()?;
+        Ok(self)
+    }
 }
 
 #[derive(Constructor)]
 pub(crate) struct Deposit {
     pub(crate) client: ClientId,
+    pub(crate) wallet: Wallet,
+    pub(crate) asset: Asset,
     pub(crate) amount: Decimal,
 }
 
 #[derive(Constructor)]
 pub(crate) struct Withdrawal {
     pub(crate) client: ClientId,
+    pub(crate) wallet: Wallet,
+    pub(crate) asset: Asset,
+    pub(crate) amount: Decimal,
+}
+
+/// Withdrawal kept for administrative reversals.
+#[derive(Constructor)]
+pub(crate) struct CompletedWithdrawal {
+    pub(crate) withdrawal: Withdrawal,
+    pub(crate) reversed: bool,
+}
+
+#[derive(Constructor)]
+pub(crate) struct ChargebackReversal {
+    pub(crate) client: ClientId,
+    pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
+}
+
+#[derive(Constructor)]
+pub(crate) struct Transfer {
+    pub(crate) from: ClientId,
+    pub(crate) from_wallet: Wallet,
+    pub(crate) to: ClientId,
+    pub(crate) to_wallet: Wallet,
+    pub(crate) asset: Asset,
+    pub(crate) amount: Decimal,
+}
+
+#[derive(Constructor)]
+pub(crate) struct Adjustment {
+    pub(crate) client: ClientId,
+    pub(crate) wallet: Wallet,
+    pub(crate) asset: Asset,
     pub(crate) amount: Decimal,
 }
 
@@ -44,28 +197,302 @@ pub(crate) struct Withdrawal {
 pub(crate) struct Dispute {
     pub(crate) client: ClientId,
     pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
 }
 
 #[derive(Constructor)]
 pub(crate) struct Resolve {
     pub(crate) client: ClientId,
     pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
 }
 
 #[derive(Constructor)]
 pub(crate) struct Chargeback {
     pub(crate) client: ClientId,
     pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
 }
 
-pub(crate) enum DisputeState {
+#[derive(Constructor)]
+pub(crate) struct Refund {
+    pub(crate) client: ClientId,
+    pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
+}
+
+#[derive(Constructor)]
+pub(crate) struct Authorization {
+    pub(crate) client: ClientId,
+    pub(crate) wallet: Wallet,
+    pub(crate) asset: Asset,
+    pub(crate) amount: Decimal,
+}
+
+#[derive(Constructor)]
+pub(crate) struct Capture {
+    pub(crate) client: ClientId,
+    pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
+}
+
+#[derive(Constructor)]
+pub(crate) struct Void {
+    pub(crate) client: ClientId,
+    pub(crate) tx: TransactionId,
+    pub(crate) asset: Option<Asset>,
+    pub(crate) amount: Option<Decimal>,
+}
+
+#[derive(Constructor)]
+pub(crate) struct EscrowOpening {
+    pub(crate) payer: ClientId,
+    pub(crate) payer_wallet: Wallet,
+    pub(crate) payee: ClientId,
+    pub(crate) payee_wallet: Wallet,
+    pub(crate) asset: Asset,
+    pub(crate) amount: Decimal,
+}
+
+#[derive(Constructor)]
+pub(crate) struct EscrowRelease {
+    pub(crate) client: ClientId,
+    pub(crate) tx: TransactionId,
+}
+
+#[derive(Constructor)]
+pub(crate) struct EscrowCancel {
+    pub(crate) client: ClientId,
+    pub(crate) tx: TransactionId,
+}
+
+/// Escrow whose funds are held on the payer until released or cancelled.
+#[derive(Constructor)]
+pub(crate) struct Escrow {
+    pub(crate) opening: EscrowOpening,
+    pub(crate) timestamp: Timestamp,
+    pub(crate) open: bool,
+}
+
+/// Authorization whose funds are held until captured or voided.
+pub(crate) struct PendingAuthorization {
+    pub(crate) authorization: Authorization,
+    /// Amount captured so far.
+    pub(crate) captured: Decimal,
+    /// Amount voided so far.
+    pub(crate) voided: Decimal,
+}
+
+impl PendingAuthorization {
+    pub(crate) fn new(authorization: Authorization) -> Self {
+        Self {
+            authorization,
+            captured: Decimal::ZERO,
+            voided: Decimal::ZERO,
+        }
+    }
+
+    /// Amount still held: neither captured nor voided.
+    pub(crate) fn remaining(&self) -> Decimal {
+        self.authorization.amount - self.captured - self.voided
+    }
+
+    /// Whether a capture or void names the same client and asset as the authorization.
+    pub(crate) fn matches(&self, client: &ClientId, asset: Option<&Asset>) -> bool {
+        self.authorization.client == *client
+            && asset.is_none_or(|asset| *asset == self.authorization.asset)
+    }
+}
+
+/// State of a disputable transaction.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeState {
+    /// Not under dispute.
     Undisputed,
+    /// Under dispute, at least partially: the disputed funds are held.
     Disputed,
+    /// Charged back, at least partially: the charged back funds left the client.
     Chargedback,
+    /// Chargeback reversed, at least partially: the reversed funds came back to the client.
+    ChargebackReversed,
+    /// Undone by an operator, it can no longer be disputed.
+    Reversed,
 }
 
-#[derive(Constructor)]
 pub(crate) struct DisputableTransaction {
+    /// The credit to the client, a deposit or the receiving end of a transfer.
     pub(crate) deposit: Deposit,
+    /// The client and wallet the funds came from, for transfers.
+    pub(crate) from: Option<(ClientId, Wallet)>,
     pub(crate) state: DisputeState,
+    pub(crate) timestamp: Timestamp,
+    /// Amount currently under dispute.
+    pub(crate) disputed: Decimal,
+    /// Amount resolved so far.
+    pub(crate) resolved: Decimal,
+    /// Amount charged back so far, reversed or not.
+    pub(crate) charged_back: Decimal,
+    /// Amount of the chargebacks reversed so far.
+    pub(crate) reversed: Decimal,
+    /// Amount refunded so far.
+    pub(crate) refunded: Decimal,
+    /// Whether an operator undid the transaction.
+    pub(crate) operator_reversed: bool,
+    /// Number of dispute cycles opened so far.
+    pub(crate) cycles: u32,
+    /// Every dispute operation applied to the transaction, in order.
+    pub(crate) history: Vec<DisputeEvent>,
+    /// When the current dispute cycle opened.
+    pub(crate) opened_at: Timestamp,
+    /// Number of transactions processed before the current dispute cycle opened.
+    pub(crate) opened_after: u64,
+}
+
+impl DisputableTransaction {
+    pub(crate) fn new(deposit: Deposit, timestamp: Timestamp) -> Self {
+        Self {
+            deposit,
+            from: None,
+            state: DisputeState::Undisputed,
+            timestamp,
+            disputed: Decimal::ZERO,
+            resolved: Decimal::ZERO,
+            charged_back: Decimal::ZERO,
+            reversed: Decimal::ZERO,
+            refunded: Decimal::ZERO,
+            operator_reversed: false,
+            cycles: 0,
+            history: Vec::new(),
+            opened_at: timestamp,
+            opened_after: 0,
+        }
+    }
+
+    /// Amount that can still be disputed or refunded: neither under dispute, charged back nor
+    /// refunded.
+    ///
+    /// Reversed chargebacks were settled by the representment and can't be disputed again, nor
+    /// can transactions undone by an operator.
+    pub(crate) fn disputable(&self) -> Decimal {
+        if self.operator_reversed {
+            return Decimal::ZERO;
+        }
+        self.deposit.amount - self.disputed - self.charged_back - self.refunded
+    }
+
+    /// Amount still credited to the client: neither refunded nor charged back, unless the
+    /// chargeback was reversed.
+    pub(crate) fn credited(&self) -> Decimal {
+        self.deposit.amount - self.refunded - self.charged_back + self.reversed
+    }
+
+    /// Derives the state from the disputed, charged back and reversed amounts.
+    fn update_state(&mut self) {
+        self.state = if self.operator_reversed {
+            DisputeState::Reversed
+        } else if self.disputed > Decimal::ZERO {
+            DisputeState::Disputed
+        } else if self.charged_back > self.reversed {
+            DisputeState::Chargedback
+        } else if self.reversed > Decimal::ZERO {
+            DisputeState::ChargebackReversed
+        } else {
+            DisputeState::Undisputed
+        };
+    }
+
+    /// Updates the state after a dispute operation and records it in the history.
+    ///
+    /// Going from not disputed to disputed opens a new dispute cycle.
+    pub(crate) fn record(
+        &mut self,
+        tx: &TransactionId,
+        r#type: Operation,
+        amount: Decimal,
+        trigger: Option<TransactionId>,
+        timestamp: Timestamp,
+    ) {
+        let was_disputed = self.state == DisputeState::Disputed;
+        self.update_state();
+        if self.state == DisputeState::Disputed && !was_disputed {
+            self.cycles += 1;
+        }
+        self.history.push(DisputeEvent {
+            tx: tx.clone(),
+            trigger,
+            r#type,
+            amount,
+            state: self.state,
+            timestamp,
+        });
+    }
+
+    /// Whether a dispute operation names the same client and asset as the deposit.
+    pub(crate) fn matches(&self, client: &ClientId, asset: Option<&Asset>) -> bool {
+        self.deposit.client == *client && asset.is_none_or(|asset| *asset == self.deposit.asset)
+    }
+}
+
+/// Age bracket of an open dispute.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum DisputeAge {
+    /// Opened less than a day ago.
+    #[serde(rename = "<1d")]
+    Day,
+    /// Opened between one and seven days ago.
+    #[serde(rename = "1d-7d")]
+    Week,
+    /// Opened between seven and thirty days ago.
+    #[serde(rename = "7d-30d")]
+    Month,
+    /// Opened thirty days ago or more.
+    #[serde(rename = ">=30d")]
+    Older,
+}
+
+impl DisputeAge {
+    /// The bracket of a dispute opened `secs` seconds ago.
+    pub fn from_secs(secs: u64) -> Self {
+        const DAY: u64 = 24 * 60 * 60;
+        match secs / DAY {
+            0 => Self::Day,
+            1..7 => Self::Week,
+            7..30 => Self::Month,
+            _ => Self::Older,
+        }
+    }
+}
+
+/// Dispute operation applied to a disputable transaction.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DisputeEvent {
+    /// The disputed transaction.
+    pub tx: TransactionId,
+    /// The transaction that triggered the operation, if any.
+    ///
+    /// Expired disputes name the transaction that found them expired, or none at the end of
+    /// the run.
+    pub trigger: Option<TransactionId>,
+    /// The operation applied.
+    pub r#type: Operation,
+    /// The amount the operation applied to.
+    pub amount: Decimal,
+    /// The state of the disputed transaction after the operation.
+    pub state: DisputeState,
+    /// When the operation was applied.
+    pub timestamp: Timestamp,
+}
+
+/// Deposit refused by its client's account state, booked into the suspense account.
+#[derive(Constructor)]
+pub(crate) struct SuspendedDeposit {
+    pub(crate) deposit: Deposit,
+    pub(crate) timestamp: Timestamp,
+    pub(crate) released: bool,
 }
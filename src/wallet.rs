@@ -0,0 +1,37 @@
+//! Wallets (sub-accounts) of clients.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Name of a client wallet, e.g. `spending` or `savings`.
+///
+/// Records without a `wallet` column address the `main` wallet.
+#[derive(Deserialize, Serialize, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Wallet(Box<str>);
+
+impl Wallet {
+    /// Whether this is the `main` wallet.
+    pub fn is_main(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for Wallet {
+    /// The `main` wallet.
+    fn default() -> Self {
+        Self("main".into())
+    }
+}
+
+impl From<&str> for Wallet {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl fmt::Display for Wallet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
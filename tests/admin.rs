@@ -0,0 +1,85 @@
+use csv::Reader;
+use toy_engine::{engine::ClientRecord, transaction::Operation, Engine};
+
+#[test]
+fn unlock_after_chargeback() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+dispute,1,1,,,
+chargeback,1,1,,,
+unlock,1,2,,ops-7,customer verified
+deposit,1,3,2,,
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_admin_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 2.into(), 0.into(), false)]
+    );
+    let log = engine.admin_log();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].operation(), Operation::Unlock);
+    assert_eq!(log[0].operator(), "ops-7");
+    assert_eq!(log[0].reason(), "customer verified");
+}
+
+#[test]
+fn lock_and_adjust() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+adjust,1,2,-1,ops-7,fee-correction
+adjust,2,3,5,ops-7,goodwill
+lock,2,4,,ops-7,fraud-suspicion
+withdrawal,2,5,1,,
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_admin_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 2.into(), 0.into(), false),
+            ClientRecord::new(2, 5.into(), 0.into(), true),
+        ]
+    );
+    assert_eq!(engine.admin_log().len(), 3);
+}
+
+#[test]
+fn admin_operation_needs_operator_and_reason() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+adjust,1,2,1,,missing-operator
+adjust,1,3,1,ops-7,
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_admin_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+    assert!(engine.admin_log().is_empty());
+}
+
+#[test]
+fn admin_operation_ignored_from_clients() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+adjust,1,2,10,ops-7,goodwill
+lock,1,3,,ops-7,fraud-suspicion
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+    assert!(engine.admin_log().is_empty());
+}
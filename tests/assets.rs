@@ -0,0 +1,81 @@
+use csv::Reader;
+use toy_engine::{
+    engine::{AssetRecord, ClientRecord},
+    Engine,
+};
+
+#[test]
+fn balances_per_asset() {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,3,USD
+deposit,1,2,2,EUR
+withdrawal,1,3,1,USD
+withdrawal,1,4,5,EUR
+deposit,2,5,4,
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.assets_ordered(),
+        vec![
+            AssetRecord::new(1, "EUR", 2.into(), 0.into(), false),
+            AssetRecord::new(1, "USD", 2.into(), 0.into(), false),
+            AssetRecord::new(2, "", 4.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn withdrawal_of_missing_asset() {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,3,USD
+withdrawal,1,2,1,EUR
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.assets_ordered(),
+        vec![AssetRecord::new(1, "USD", 3.into(), 0.into(), false)]
+    );
+}
+
+#[test]
+fn dispute_holds_deposit_asset() {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,3,USD
+deposit,1,2,2,EUR
+dispute,1,1,,
+dispute,1,2,,USD
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.assets_ordered(),
+        vec![
+            AssetRecord::new(1, "EUR", 2.into(), 0.into(), false),
+            AssetRecord::new(1, "USD", 0.into(), 3.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn accounts_report_default_asset() {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,3,USD
+deposit,1,2,2,EUR
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default().with_default_asset("USD".into());
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::{engine, load};
+use toy_engine::{
+    engine::{ClientRecord, HoldRecord},
+    HoldReason,
+};
+
+#[test]
+fn authorization_holds_available_funds() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+authorize,1,2,3
+authorize,1,3,3
+withdrawal,1,4,3
+";
+    let engine = load(engine(), data);
+    // The second authorization and the withdrawal exceed the available funds.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 2.into(), 3.into(), false)]
+    );
+    assert_eq!(
+        engine.holds_ordered(),
+        vec![HoldRecord::new(
+            1,
+            "",
+            HoldReason::Authorization,
+            1,
+            3.into()
+        )]
+    );
+}
+
+#[test]
+fn capture_withdraws_held_funds() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+authorize,1,2,3
+capture,1,2,1
+capture,1,2,
+capture,1,2,1
+void,1,2,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 2.into(), 0.into(), false)]
+    );
+    assert!(engine.holds_ordered().is_empty());
+}
+
+#[test]
+fn void_releases_held_funds() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+authorize,1,2,3
+void,2,2,
+void,1,2,1
+capture,1,2,
+void,1,2,
+";
+    let engine = load(engine(), data);
+    // The void naming another client is refused, the capture takes what's left after the void.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+}
+
+#[test]
+fn locked_accounts_refuse_authorizations() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+authorize,1,2,1
+dispute,1,1,2
+chargeback,1,1,
+authorize,1,3,1
+capture,1,2,
+";
+    let engine = load(engine(), data);
+    // Pending authorizations can still be captured.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 2.into(), 0.into(), true)]
+    );
+}
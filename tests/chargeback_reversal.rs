@@ -0,0 +1,97 @@
+mod common;
+
+use common::{engine, load};
+use toy_engine::{
+    engine::{ClientRecord, TransactionReport},
+    transaction::DisputeState,
+    LockPolicy, Timestamp,
+};
+
+const DATA: &str = "\
+type,client,tx,amount
+deposit,1,1,3
+dispute,1,1,
+chargeback,1,1,
+chargeback_reversal,1,1,
+deposit,1,2,1
+";
+
+#[test]
+fn reversal_keeps_lock_by_default() {
+    let engine = load(engine().with_lock_policy(LockPolicy::default()), DATA);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), true)]
+    );
+    assert_eq!(
+        engine.transactions_ordered(),
+        vec![TransactionReport::new(
+            1,
+            1,
+            "",
+            3.into(),
+            DisputeState::ChargebackReversed,
+            Timestamp::from_secs(1)
+        )
+        .with_amounts(0.into(), 0.into(), 3.into())
+        .with_reversed(3.into())]
+    );
+}
+
+#[test]
+fn reversal_unlocks_with_policy() {
+    let policy = LockPolicy {
+        reversal_unlocks: true,
+        ..Default::default()
+    };
+    let engine = load(engine().with_lock_policy(policy), DATA);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 4.into(), 0.into(), false)]
+    );
+}
+
+#[test]
+fn partial_reversal() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,3
+dispute,1,1,
+chargeback,1,1,
+chargeback_reversal,1,1,1
+chargeback_reversal,1,1,5
+dispute,1,1,
+";
+    let engine = load(engine().with_lock_policy(LockPolicy::default()), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 1.into(), 0.into(), true)]
+    );
+    assert_eq!(
+        engine.transactions_ordered()[0],
+        TransactionReport::new(
+            1,
+            1,
+            "",
+            3.into(),
+            DisputeState::Chargedback,
+            Timestamp::from_secs(1)
+        )
+        .with_reversed(1.into())
+    );
+}
+
+#[test]
+fn reversal_without_chargeback_ignored() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,3
+dispute,1,1,
+chargeback_reversal,1,1,
+";
+    let engine = load(engine().with_lock_policy(LockPolicy::default()), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 3.into(), false)]
+    );
+}
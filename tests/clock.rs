@@ -0,0 +1,75 @@
+use csv::Reader;
+use toy_engine::{
+    clock::{Date, FixedClock},
+    engine::{ClientRecord, TransactionReport},
+    transaction::DisputeState,
+    Engine, Timestamp,
+};
+
+#[test]
+fn timestamps_stored_on_transactions() {
+    let data = "\
+type,client,tx,amount,timestamp
+deposit,1,1,3,100
+deposit,1,2,1,
+dispute,1,1,,200
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default().with_clock(Box::new(FixedClock(Timestamp::from_secs(150))));
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.transactions_ordered(),
+        vec![
+            TransactionReport::new(
+                1,
+                1,
+                "",
+                3.into(),
+                DisputeState::Disputed,
+                Timestamp::from_secs(100)
+            ),
+            TransactionReport::new(
+                2,
+                1,
+                "",
+                1.into(),
+                DisputeState::Undisputed,
+                Timestamp::from_secs(150)
+            ),
+        ]
+    );
+    let client = engine.clients().values().next().unwrap();
+    assert_eq!(client.last_timestamp(), Some(Timestamp::from_secs(200)));
+}
+
+#[test]
+fn non_monotonic_transaction_ignored() {
+    let data = "\
+type,client,tx,amount,timestamp
+deposit,1,1,3,100
+deposit,2,2,3,50
+withdrawal,1,3,1,99
+deposit,1,4,1,100
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_from_reader(reader).unwrap();
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 4.into(), 0.into(), false),
+            ClientRecord::new(2, 3.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn calendar_dates() {
+    let date = |secs| Timestamp::from_secs(secs).date();
+    let expected = |year, month, day| Date { year, month, day };
+    assert_eq!(date(0), expected(1970, 1, 1));
+    assert_eq!(date(1_706_745_599), expected(2024, 1, 31));
+    assert_eq!(date(1_709_164_800), expected(2024, 2, 29));
+    assert_eq!(date(1_709_251_200), expected(2024, 3, 1));
+    assert_eq!(date(1_735_689_599), expected(2024, 12, 31));
+}
@@ -0,0 +1,36 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use csv::Reader;
+use toy_engine::{clock::FixedClock, Engine, InvariantCheck, Timestamp};
+
+/// Engine checking the invariants after each transaction, records without a timestamp dated 1.
+pub fn engine() -> Engine {
+    engine_at(1)
+}
+
+/// Engine checking the invariants after each transaction, records without a timestamp dated
+/// `now`.
+pub fn engine_at(now: u64) -> Engine {
+    Engine::default()
+        .with_invariant_check(InvariantCheck::EachTransaction)
+        .with_clock(Box::new(FixedClock(Timestamp::from_secs(now))))
+}
+
+/// Reads CSV data from a string.
+pub fn reader(data: &str) -> Reader<&[u8]> {
+    Reader::from_reader(data.as_bytes())
+}
+
+/// Loads client records into an engine.
+pub fn load(mut engine: Engine, data: &str) -> Engine {
+    engine.load_from_reader(reader(data)).unwrap();
+    engine
+}
+
+/// Loads an operator stream, administrative operations included, into an engine.
+pub fn load_admin(mut engine: Engine, data: &str) -> Engine {
+    engine.load_admin_from_reader(reader(data)).unwrap();
+    engine
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{engine, load, reader};
+use csv::Writer;
+use rust_decimal::Decimal;
+use toy_engine::{engine::ClientRecord, CreditLimits, Engine, Error, IdKind};
+
+const LIMITS: &str = "\
+client,limit,asset
+1,5,
+2,3,ETH
+";
+
+fn engine_with_credit(limits: &str) -> Engine {
+    let limits = CreditLimits::load_from_reader(reader(limits), IdKind::Numeric).unwrap();
+    engine().with_credit_limits(limits)
+}
+
+#[test]
+fn withdrawals_draw_on_the_credit_line() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,2
+withdrawal,1,2,4
+withdrawal,1,3,4
+withdrawal,1,4,3
+deposit,2,5,2
+withdrawal,2,6,3
+";
+    let engine = load(engine_with_credit(LIMITS), data);
+    // Client 1 can go down to -5, client 2 only has credit in ETH.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, Decimal::from(-5), 0.into(), false).with_credit(5.into()),
+            ClientRecord::new(2, 2.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn credit_lines_per_asset() {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,1,
+deposit,2,2,1,
+withdrawal,2,3,3,ETH
+withdrawal,2,4,1,ETH
+withdrawal,1,5,1,ETH
+";
+    let engine = load(engine_with_credit(LIMITS), data);
+    let balance = engine.clients()[&2.into()].balance(&"ETH".into()).unwrap();
+    assert_eq!(balance.available(), Decimal::from(-3));
+    assert!(engine.clients()[&1.into()].balance(&"ETH".into()).is_none());
+}
+
+#[test]
+fn negative_balances_beyond_the_credit_line_are_not_credit() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,3
+withdrawal,1,2,7
+dispute,1,1,
+";
+    let engine = load(engine_with_credit(LIMITS), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, Decimal::from(-7), 3.into(), false).with_credit(5.into())]
+    );
+}
+
+#[test]
+fn negative_limits_are_refused() {
+    let limits = "client,limit,asset\n1,-1,\n";
+    assert!(matches!(
+        CreditLimits::load_from_reader(reader(limits), IdKind::Numeric),
+        Err(Error::InvalidCreditLimit(_))
+    ));
+}
+
+#[test]
+fn accounts_output_reports_zero_credit() {
+    let data = "\
+type,client,tx,amount,wallet
+deposit,1,1,2,
+withdrawal,1,2,2,
+deposit,1,3,1,savings
+";
+    let engine = load(engine_with_credit(LIMITS), data);
+    let mut output = Vec::new();
+    engine
+        .dump_accounts(Writer::from_writer(&mut output))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+client,wallet,available,held,total,credit,locked,state
+1,,1,0,1,0,false,active
+1,main,0,0,0,0,false,active
+1,savings,1,0,1,0,false,active
+"
+    );
+}
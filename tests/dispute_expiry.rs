@@ -0,0 +1,137 @@
+mod common;
+
+use common::{engine_at, load};
+use rust_decimal::Decimal;
+use toy_engine::{
+    engine::{AgingRecord, ClientRecord},
+    policy::ExpiryAction,
+    transaction::{DisputeAge, DisputeState, Operation},
+    DisputePolicy, Timestamp,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+const DATA: &str = "\
+type,client,tx,amount,timestamp
+deposit,1,1,3,10
+dispute,1,1,,20
+deposit,2,2,1,30
+deposit,2,3,1,40
+";
+
+#[test]
+fn disputes_expire_after_time() {
+    let policy = DisputePolicy {
+        expire_after: Some(20),
+        ..Default::default()
+    };
+    let engine = load(engine_at(40).with_dispute_policy(policy), DATA);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false),
+            ClientRecord::new(2, 2.into(), 0.into(), false),
+        ]
+    );
+    // The deposit arriving when the dispute is 20 seconds old expires it.
+    let event = engine.dispute_history(&1.into()).unwrap()[1].clone();
+    assert_eq!(event.r#type, Operation::Resolve);
+    assert_eq!(event.trigger, Some(3.into()));
+    assert_eq!(event.timestamp, Timestamp::from_secs(40));
+}
+
+#[test]
+fn disputes_expire_after_transactions_into_chargebacks() {
+    let policy = DisputePolicy {
+        expire_after_transactions: Some(2),
+        on_expiry: ExpiryAction::Chargeback,
+        ..Default::default()
+    };
+    let engine = load(engine_at(40).with_dispute_policy(policy), DATA);
+    // Two transactions followed the dispute, it expires at the end of the run.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 0.into(), 0.into(), true),
+            ClientRecord::new(2, 2.into(), 0.into(), false),
+        ]
+    );
+    let event = engine.dispute_history(&1.into()).unwrap()[1].clone();
+    assert_eq!(event.r#type, Operation::Chargeback);
+    assert_eq!(event.state, DisputeState::Chargedback);
+    assert_eq!(event.trigger, None);
+}
+
+#[test]
+fn disputes_closed_in_time_do_not_expire() {
+    let data = "\
+type,client,tx,amount,timestamp
+deposit,1,1,3,10
+dispute,1,1,,20
+resolve,1,1,,30
+dispute,1,1,,40
+";
+    let policy = DisputePolicy {
+        expire_after: Some(25),
+        expire_after_transactions: Some(3),
+        ..Default::default()
+    };
+    let engine = load(engine_at(50).with_dispute_policy(policy), data);
+    // The second cycle started the count over.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 3.into(), false)]
+    );
+    assert_eq!(engine.dispute_history(&1.into()).unwrap().len(), 3);
+}
+
+#[test]
+fn end_of_run_expiry_ignores_the_time_of_the_run() {
+    let policy = DisputePolicy {
+        expire_after: Some(30 * DAY),
+        ..Default::default()
+    };
+    // The run happens long after the records, whose latest is when the dispute is 20 seconds old.
+    let engine = load(engine_at(365 * DAY).with_dispute_policy(policy), DATA);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 0.into(), 3.into(), false),
+            ClientRecord::new(2, 2.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn held_funds_by_dispute_age() {
+    let data = format!(
+        "\
+type,client,tx,amount,asset,timestamp
+deposit,1,1,3,BTC,0
+deposit,1,2,2,BTC,0
+deposit,2,3,1,BTC,0
+deposit,2,4,5,ETH,0
+dispute,1,1,,,0
+dispute,1,2,1,,{}
+dispute,2,3,,,{}
+dispute,2,4,,,{}
+",
+        20 * DAY,
+        25 * DAY,
+        29 * DAY + 1
+    );
+    let now = 30 * DAY;
+    let engine = load(
+        engine_at(now).with_dispute_policy(DisputePolicy::default()),
+        &data,
+    );
+    assert_eq!(
+        engine.dispute_aging(Timestamp::from_secs(now)),
+        vec![
+            AgingRecord::new("BTC", DisputeAge::Week, 1, Decimal::ONE),
+            AgingRecord::new("BTC", DisputeAge::Month, 1, Decimal::ONE),
+            AgingRecord::new("BTC", DisputeAge::Older, 1, 3.into()),
+            AgingRecord::new("ETH", DisputeAge::Day, 1, 5.into()),
+        ]
+    );
+}
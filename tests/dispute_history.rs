@@ -0,0 +1,108 @@
+mod common;
+
+use common::{engine, load};
+use toy_engine::{
+    engine::ClientRecord,
+    transaction::{DisputeEvent, DisputeState, Operation},
+    DisputePolicy, Timestamp,
+};
+
+fn event(r#type: Operation, amount: u32, state: DisputeState, timestamp: u64) -> DisputeEvent {
+    DisputeEvent {
+        tx: 1.into(),
+        trigger: Some(1.into()),
+        r#type,
+        amount: amount.into(),
+        state,
+        timestamp: Timestamp::from_secs(timestamp),
+    }
+}
+
+const DATA: &str = "\
+type,client,tx,amount,timestamp
+deposit,1,1,3,1
+dispute,1,1,,2
+resolve,1,1,,3
+dispute,1,1,1,4
+dispute,1,1,1,5
+resolve,1,1,,6
+dispute,1,1,,7
+";
+
+#[test]
+fn history_lists_every_transition() {
+    let engine = load(engine().with_dispute_policy(DisputePolicy::default()), DATA);
+    assert_eq!(
+        engine.dispute_history(&1.into()).unwrap(),
+        [
+            event(Operation::Dispute, 3, DisputeState::Disputed, 2),
+            event(Operation::Resolve, 3, DisputeState::Undisputed, 3),
+            event(Operation::Dispute, 1, DisputeState::Disputed, 4),
+            event(Operation::Dispute, 1, DisputeState::Disputed, 5),
+            event(Operation::Resolve, 2, DisputeState::Undisputed, 6),
+            event(Operation::Dispute, 3, DisputeState::Disputed, 7),
+        ]
+    );
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 3.into(), false)]
+    );
+}
+
+#[test]
+fn disputes_refused_after_max_cycles() {
+    let policy = DisputePolicy {
+        max_cycles: 2,
+        ..Default::default()
+    };
+    let engine = load(engine().with_dispute_policy(policy), DATA);
+    // The second cycle allows several partial disputes, a third cycle is refused.
+    let history = engine.dispute_history(&1.into()).unwrap();
+    assert_eq!(history.len(), 5);
+    assert_eq!(history[4].state, DisputeState::Undisputed);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+}
+
+#[test]
+fn refused_operations_are_not_recorded() {
+    let data = "\
+type,client,tx,amount,timestamp
+deposit,1,1,3,1
+resolve,1,1,,2
+dispute,1,1,4,3
+dispute,2,1,,4
+chargeback,1,1,,5
+";
+    let engine = load(engine().with_dispute_policy(DisputePolicy::default()), data);
+    assert_eq!(engine.dispute_history(&1.into()).unwrap(), []);
+    assert!(engine.dispute_history(&2.into()).is_none());
+}
+
+#[test]
+fn history_ordered_across_transactions() {
+    let data = "\
+type,client,tx,amount,timestamp
+deposit,1,2,1,1
+deposit,1,1,1,2
+dispute,1,1,,3
+dispute,1,2,,4
+chargeback,1,2,,5
+";
+    let engine = load(engine().with_dispute_policy(DisputePolicy::default()), data);
+    let history: Vec<_> = engine
+        .dispute_history_ordered()
+        .into_iter()
+        .map(|event| (event.tx, event.r#type))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            (2.into(), Operation::Dispute),
+            (2.into(), Operation::Chargeback),
+            (1.into(), Operation::Dispute),
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{engine, load};
+use toy_engine::engine::ClientRecord;
+
+#[test]
+fn released_escrows_pay_the_payee() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+escrow_open,1,3,2,2
+escrow_release,1,3,,
+escrow_release,1,3,,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false),
+            ClientRecord::new(2, 3.into(), 0.into(), false),
+        ]
+    );
+    assert!(engine.escrows_ordered().is_empty());
+}
+
+#[test]
+fn cancelled_escrows_return_to_the_payer() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+escrow_open,1,3,2,2
+escrow_release,2,3,,
+escrow_cancel,1,3,,
+escrow_release,1,3,,
+";
+    let engine = load(engine(), data);
+    // Only the payer can release or cancel, and a cancelled escrow can't be released.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 5.into(), 0.into(), false),
+            ClientRecord::new(2, 1.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn open_escrows_are_held_and_listed() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+escrow_open,1,3,2,2
+escrow_open,1,4,4,2
+escrow_open,1,5,1,3
+escrow_open,2,6,1,1
+withdrawal,1,7,4,
+";
+    let engine = load(engine(), data);
+    // Escrows and withdrawals exceeding the available funds, or naming an unknown payee, are refused.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 2.into(), false),
+            ClientRecord::new(2, 0.into(), 1.into(), false),
+        ]
+    );
+    let escrows: Vec<_> = engine
+        .escrows_ordered()
+        .iter()
+        .map(|escrow| {
+            (
+                escrow.tx().clone(),
+                escrow.payer().clone(),
+                escrow.payee().clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        escrows,
+        vec![
+            (3.into(), 1.into(), 2.into()),
+            (6.into(), 2.into(), 1.into())
+        ]
+    );
+}
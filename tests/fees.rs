@@ -0,0 +1,233 @@
+mod common;
+
+use common::{engine, load, load_admin, reader};
+use rust_decimal::Decimal;
+use toy_engine::{
+    engine::ClientRecord,
+    fee::{Fee, FeeSchedule},
+    interest::InterestRates,
+    transaction::Operation,
+    Engine, Error,
+};
+
+const FEES: &str = "\
+operation,asset,flat,percent,min,max
+withdrawal,,0.5,1,,2
+transfer,,,10,1,
+chargeback,,15,,,
+";
+
+fn engine_with_fees(fees: &str) -> Engine {
+    let fees = FeeSchedule::load_from_reader(reader(fees)).unwrap();
+    engine().with_fees(fees, "house".into())
+}
+
+#[test]
+fn fees_credited_to_the_house() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,300,
+deposit,2,2,1,
+withdrawal,1,3,100,
+withdrawal,1,4,10,
+transfer,1,5,5,2
+";
+    let engine = load(engine_with_fees(FEES), data);
+    // Fees of 1.5 (0.5 + 1%), 0.6 and 1 (the minimum).
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, Decimal::new(1819, 1), 0.into(), false),
+            ClientRecord::new(2, 6.into(), 0.into(), false),
+            ClientRecord::new("house", Decimal::new(31, 1), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn transactions_refused_when_the_fee_is_not_available() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,10,
+deposit,2,2,1,
+withdrawal,1,3,10,
+transfer,1,4,9.5,2
+transfer,1,5,9,2
+";
+    let engine = load(engine_with_fees(FEES), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 0.into(), 0.into(), false),
+            ClientRecord::new(2, 10.into(), 0.into(), false),
+            ClientRecord::new("house", 1.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn deposits_below_their_fee_are_suspended() {
+    let fees = "operation,asset,flat,percent,min,max\ndeposit,,1,,,\n";
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+deposit,1,2,0.5
+";
+    let engine = load(engine_with_fees(fees), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 4.into(), 0.into(), false),
+            ClientRecord::new("house", 1.into(), 0.into(), false),
+        ]
+    );
+    let suspense: Vec<_> = engine
+        .suspense_ordered()
+        .iter()
+        .map(|record| (record.tx().clone(), record.amount()))
+        .collect();
+    assert_eq!(suspense, vec![(2.into(), Decimal::new(5, 1))]);
+}
+
+#[test]
+fn released_deposits_pay_their_fee() {
+    let fees = "operation,asset,flat,percent,min,max\ndeposit,,2,,,\n";
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,10,,
+deposit,1,2,1,,
+release,1,2,,ops-7,fee waived
+lock,1,3,,ops-7,kyc review
+deposit,1,4,5,,
+unlock,1,5,,ops-7,kyc done
+release,1,4,,ops-7,kyc done
+";
+    let engine = load_admin(engine_with_fees(fees), data);
+    // The deposit below its fee stays suspended, the other one pays its fee when released.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 11.into(), 0.into(), false),
+            ClientRecord::new("house", 4.into(), 0.into(), false),
+        ]
+    );
+    let suspense: Vec<_> = engine
+        .suspense_ordered()
+        .iter()
+        .map(|record| (record.tx().clone(), record.released()))
+        .collect();
+    assert_eq!(suspense, vec![(2.into(), false), (4.into(), true)]);
+}
+
+#[test]
+fn chargebacks_charge_a_penalty() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,10
+deposit,1,2,5
+dispute,1,1,
+chargeback,1,1,
+";
+    let engine = load(engine_with_fees(FEES), data);
+    // The penalty applies even though it exceeds the remaining funds.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, Decimal::from(-10), 0.into(), true),
+            ClientRecord::new("house", 15.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn fee_amounts_and_validation() {
+    let fee = Fee {
+        operation: Operation::Deposit,
+        asset: None,
+        flat: None,
+        percent: Some(Decimal::new(25, 2)),
+        min: None,
+        max: None,
+    };
+    // 0.25% of 1.23 is 0.003075, rounded at four places.
+    assert_eq!(fee.amount(Decimal::new(123, 2)), Decimal::new(31, 4));
+    let fees = "operation,asset,flat,percent,min,max\ndeposit,,,,3,2\n";
+    assert!(matches!(
+        FeeSchedule::load_from_reader(reader(fees)),
+        Err(Error::InvalidFee(Operation::Deposit))
+    ));
+    let fees = "operation,asset,flat,percent,min,max\ndispute,,1,,,\n";
+    assert!(matches!(
+        FeeSchedule::load_from_reader(reader(fees)),
+        Err(Error::InvalidFee(Operation::Dispute))
+    ));
+}
+
+const HOUSE_FEES: &str = "\
+operation,asset,flat,percent,min,max
+deposit,,1,,,
+withdrawal,,1,,,
+";
+
+fn engine_with_house(house: u64) -> Engine {
+    let fees = FeeSchedule::load_from_reader(reader(HOUSE_FEES)).unwrap();
+    engine().with_fees(fees, house.into())
+}
+
+#[test]
+fn client_records_cannot_move_house_funds() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,10,
+withdrawal,0,2,1,
+deposit,0,3,5,
+transfer,1,4,2,0
+";
+    let engine = load(engine_with_house(0), data);
+    // Only the fee of the first deposit reaches the house, which pays no fee either.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(0, 1.into(), 0.into(), false),
+            ClientRecord::new(1, 9.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn house_account_earns_no_interest() {
+    let rates = InterestRates::load_from_reader(reader("tier,asset,rate,convention\n,,10,\n"));
+    let data = "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,11,0,,
+accrue,*,2,,31536000,ops-7,yearly interest
+";
+    let engine = load_admin(
+        engine_with_house(0).with_interest_rates(rates.unwrap()),
+        data,
+    );
+    let postings: Vec<_> = engine
+        .interest_postings()
+        .iter()
+        .map(|posting| (posting.client().clone(), posting.amount()))
+        .collect();
+    assert_eq!(postings, vec![(1.into(), 1.into())]);
+}
+
+#[test]
+fn house_account_never_goes_dormant() {
+    let data = "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,10,100,,
+adjust,0,2,1,100,ops-7,opening balance
+deposit,1,3,1,200,,
+";
+    let engine = load_admin(engine_with_house(0).with_dormant_after(50), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(0, 3.into(), 0.into(), false),
+            ClientRecord::new(1, 9.into(), 0.into(), false),
+        ]
+    );
+}
@@ -0,0 +1,96 @@
+use csv::{Reader, ReaderBuilder, Trim};
+use rust_decimal::Decimal;
+use toy_engine::{engine::ConsolidatedRecord, Engine, RateTable, Timestamp};
+
+const RATES: &str = "\
+pair, rate, effective
+EUR/USD, 1.10, 100
+EUR/USD, 1.20, 200
+USD/JPY, 150, 100
+";
+
+fn engine() -> Engine {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,10,EUR
+deposit,1,2,5,USD
+deposit,2,3,1000,JPY
+dispute,2,3,,
+";
+    let mut engine = Engine::default();
+    engine
+        .load_from_reader(Reader::from_reader(data.as_bytes()))
+        .unwrap();
+    engine
+}
+
+fn rates() -> RateTable {
+    let reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .from_reader(RATES.as_bytes());
+    RateTable::load_from_reader(reader).unwrap()
+}
+
+#[test]
+fn consolidated_latest_rates() {
+    let consolidation = engine()
+        .clients_consolidated(&rates(), &"USD".into(), None)
+        .unwrap();
+    assert_eq!(
+        consolidation.records,
+        vec![
+            ConsolidatedRecord::new(1, "USD", 17.into(), 0.into(), false),
+            // 1000 / 150 rounded half to even.
+            ConsolidatedRecord::new(2, "USD", 0.into(), Decimal::new(66667, 4), false),
+        ]
+    );
+    let pairs: Vec<_> = consolidation
+        .rates
+        .iter()
+        .map(|rate| (rate.pair.to_string(), rate.effective.as_secs()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![("EUR/USD".to_string(), 200), ("USD/JPY".to_string(), 100)]
+    );
+}
+
+#[test]
+fn consolidated_rates_at_time() {
+    let consolidation = engine()
+        .clients_consolidated(&rates(), &"USD".into(), Some(Timestamp::from_secs(150)))
+        .unwrap();
+    assert_eq!(
+        consolidation.records[0],
+        ConsolidatedRecord::new(1, "USD", 16.into(), 0.into(), false)
+    );
+}
+
+#[test]
+fn consolidated_missing_rate() {
+    assert!(engine()
+        .clients_consolidated(&rates(), &"USD".into(), Some(Timestamp::from_secs(50)))
+        .is_err());
+    assert!(engine()
+        .clients_consolidated(&rates(), &"GBP".into(), None)
+        .is_err());
+}
+
+#[test]
+fn consolidated_dump_metadata() {
+    let mut out = Vec::new();
+    engine()
+        .dump_consolidated(&mut out, &rates(), &"USD".into(), None)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\
+# base: USD
+# rate: EUR/USD 1.2 effective 200
+# rate: USD/JPY 150 effective 100
+client,base,available,held,total,locked,state
+1,USD,17.0,0,17.0,false,active
+2,USD,0,6.6667,6.6667,false,active
+"
+    );
+}
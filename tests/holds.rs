@@ -0,0 +1,74 @@
+mod common;
+
+use common::{engine, load};
+use rust_decimal::Decimal;
+use toy_engine::{
+    engine::{ClientId, HoldRecord},
+    hold::Hold,
+    HoldReason, Wallet,
+};
+
+const DATA: &str = "\
+type,client,tx,amount,asset
+deposit,1,1,3,BTC
+deposit,1,2,2,BTC
+deposit,1,3,5,ETH
+deposit,2,4,1,BTC
+dispute,1,1,1,
+dispute,1,1,1,
+dispute,1,2,,
+dispute,1,3,,
+dispute,2,4,,
+resolve,1,3,,
+chargeback,2,4,,
+";
+
+#[test]
+fn holds_sum_to_held_funds() {
+    let engine = load(engine(), DATA);
+    let client = &engine.clients()[&ClientId::from(1)];
+    assert_eq!(
+        client.holds(),
+        [
+            Hold {
+                wallet: Wallet::default(),
+                asset: "BTC".into(),
+                reason: HoldReason::Dispute,
+                tx: 1.into(),
+                amount: 2.into(),
+            },
+            Hold {
+                wallet: Wallet::default(),
+                asset: "BTC".into(),
+                reason: HoldReason::Dispute,
+                tx: 2.into(),
+                amount: 2.into(),
+            },
+        ]
+    );
+    assert_eq!(client.balance(&"BTC".into()).unwrap().held(), 4.into());
+    assert_eq!(client.balance(&"ETH".into()).unwrap().held(), Decimal::ZERO);
+}
+
+#[test]
+fn held_funds_per_reason() {
+    let engine = load(engine(), DATA);
+    assert_eq!(
+        engine.holds_ordered(),
+        vec![HoldRecord::new(1, "BTC", HoldReason::Dispute, 2, 4.into())]
+    );
+}
+
+#[test]
+fn released_holds_are_removed() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,3
+dispute,1,1,2
+resolve,1,1,1
+chargeback,1,1,1
+";
+    let engine = load(engine(), data);
+    assert!(engine.clients()[&ClientId::from(1)].holds().is_empty());
+    assert!(engine.holds_ordered().is_empty());
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use common::{engine_at, load_admin, reader};
+use rust_decimal::Decimal;
+use toy_engine::{
+    engine::{ClientId, ClientRecord},
+    interest::{DayCount, InterestRates},
+    ClientTiers, Engine, IdKind, Timestamp,
+};
+
+// 2024-01-01 and 2025-01-01, a leap year apart.
+const START: u64 = 1_704_067_200;
+const END: u64 = 1_735_689_600;
+const DAY: u64 = 24 * 60 * 60;
+
+const RATES: &str = "\
+tier,asset,rate,convention
+,,10,
+premium,,20,30/360
+";
+
+fn engine_with_rates(rates: &str) -> Engine {
+    let tiers = "client,tier\n2,premium\n";
+    let tiers = ClientTiers::load_from_reader(reader(tiers), IdKind::Numeric).unwrap();
+    let rates = InterestRates::load_from_reader(reader(rates)).unwrap();
+    engine_at(END)
+        .with_client_tiers(tiers)
+        .with_interest_rates(rates)
+}
+
+fn postings(engine: &Engine) -> Vec<(ClientId, Decimal)> {
+    engine
+        .interest_postings()
+        .iter()
+        .map(|posting| (posting.client().clone(), posting.amount()))
+        .collect()
+}
+
+#[test]
+fn runs_credit_interest_per_tier() {
+    let data = format!(
+        "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,1000,{START},,
+deposit,2,2,1000,{START},,
+accrue,*,3,,{END},ops-7,yearly interest
+"
+    );
+    let engine = load_admin(engine_with_rates(RATES), &data);
+    // Client 1 earns 10% over 366/365 of a year, premium client 2 earns 20% over a 30/360 year.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, Decimal::new(11_002_740, 4), 0.into(), false),
+            ClientRecord::new(2, 1200.into(), 0.into(), false),
+        ]
+    );
+    assert_eq!(
+        postings(&engine),
+        vec![
+            (1.into(), Decimal::new(1_002_740, 4)),
+            (2.into(), 200.into())
+        ]
+    );
+}
+
+#[test]
+fn balances_accrue_for_the_days_they_are_held() {
+    let data = format!(
+        "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,1000,{START},,
+withdrawal,1,2,500,{HALF},,
+deposit,3,3,1000,{WEEK_BEFORE_END},,
+accrue,*,4,,{END},ops-7,yearly interest
+",
+        HALF = START + 183 * DAY,
+        WEEK_BEFORE_END = END - 7 * DAY,
+    );
+    let engine = load_admin(engine_with_rates(RATES), &data);
+    // Client 1 held 1000 for 183 days then 500 for 183 days, client 3 held 1000 for 7 days.
+    assert_eq!(
+        postings(&engine),
+        vec![
+            (1.into(), Decimal::new(752_055, 4)),
+            (3.into(), Decimal::new(19_178, 4))
+        ]
+    );
+}
+
+#[test]
+fn runs_post_what_accrued_since_the_previous_one() {
+    let data = format!(
+        "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,1000,{START},,
+accrue,*,2,,{HALF},ops-7,half-yearly interest
+accrue,*,3,,{END},ops-7,half-yearly interest
+",
+        HALF = START + 183 * DAY,
+    );
+    let engine = load_admin(engine_with_rates(RATES), &data);
+    // Interest posted by the first run earns interest too, and rounding remainders carry over.
+    assert_eq!(
+        postings(&engine),
+        vec![
+            (1.into(), Decimal::new(501_370, 4)),
+            (1.into(), Decimal::new(526_507, 4))
+        ]
+    );
+    let remainder = engine.clients()[&1.into()].accrued(&"main".into(), &"".into());
+    assert!(remainder.abs() < Decimal::new(5, 5));
+}
+
+#[test]
+fn held_funds_earn_nothing() {
+    let data = format!(
+        "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,1000,{START},,
+deposit,1,2,500,{START},,
+dispute,1,1,,{START},,
+accrue,*,3,,{END},ops-7,yearly interest
+"
+    );
+    let engine = load_admin(engine_with_rates(RATES), &data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(
+            1,
+            Decimal::new(5_501_370, 4),
+            1000.into(),
+            false
+        )]
+    );
+}
+
+#[test]
+fn closing_posts_accrued_interest_before_the_payout() {
+    let data = format!(
+        "\
+type,client,tx,amount,timestamp,operator,reason
+deposit,1,1,1000,{START},,
+close,1,2,,{END},ops-7,customer request
+"
+    );
+    let engine = load_admin(engine_with_rates(RATES), &data);
+    assert_eq!(
+        postings(&engine),
+        vec![(1.into(), Decimal::new(1_002_740, 4))]
+    );
+    let payouts: Vec<_> = engine
+        .admin_log()
+        .iter()
+        .map(|entry| entry.amount().unwrap())
+        .collect();
+    assert_eq!(payouts, vec![Decimal::new(11_002_740, 4)]);
+}
+
+#[test]
+fn day_count_conventions() {
+    // 2024-01-31 to 2024-03-01: 30 actual days, 31 days counting 30 per month.
+    let (start, end) = (
+        Timestamp::from_secs(1_706_659_200),
+        Timestamp::from_secs(1_709_251_200),
+    );
+    assert_eq!(
+        DayCount::Actual360.year_fraction(start, end),
+        Decimal::from(30) / Decimal::from(360)
+    );
+    assert_eq!(
+        DayCount::Actual365.year_fraction(start, end),
+        Decimal::from(30) / Decimal::from(365)
+    );
+    assert_eq!(
+        DayCount::Thirty360.year_fraction(start, end),
+        Decimal::from(31) / Decimal::from(360)
+    );
+}
@@ -0,0 +1,94 @@
+use csv::Reader;
+use toy_engine::{invariant::Violation, Asset, Engine, Error, InvariantCheck};
+
+const DATA: &str = "\
+type,client,tx,amount
+deposit,1,1,3
+withdrawal,1,2,1
+dispute,1,1,
+deposit,2,3,5
+dispute,2,3,
+chargeback,2,3,
+deposit,3,4,2
+dispute,3,4,
+resolve,3,4,
+";
+
+#[test]
+fn invariants_hold_each_transaction() {
+    let reader = Reader::from_reader(DATA.as_bytes());
+    let mut engine = Engine::default().with_invariant_check(InvariantCheck::EachTransaction);
+    engine.load_from_reader(reader).unwrap();
+}
+
+#[test]
+fn invariants_hold_end_of_run() {
+    let reader = Reader::from_reader(DATA.as_bytes());
+    let mut engine = Engine::default().with_invariant_check(InvariantCheck::EndOfRun);
+    engine.load_from_reader(reader).unwrap();
+}
+
+#[test]
+fn client_total_tracks_balances() {
+    let reader = Reader::from_reader(DATA.as_bytes());
+    let mut engine = Engine::default().with_invariant_check(InvariantCheck::Off);
+    engine.load_from_reader(reader).unwrap();
+    let sum: rust_decimal::Decimal = engine
+        .clients()
+        .values()
+        .flat_map(|data| data.balances())
+        .map(|(_, balance)| balance.total())
+        .sum();
+    assert_eq!(sum, 4.into());
+}
+
+/// Loads a deposit for client 1, then corrupts its balance.
+fn corrupted(invariant_check: InvariantCheck) -> Engine {
+    let reader = Reader::from_reader("type,client,tx,amount\ndeposit,1,1,3\n".as_bytes());
+    let mut engine = Engine::default().with_invariant_check(invariant_check);
+    engine.load_from_reader(reader).unwrap();
+    engine.corrupt_balance(&1.into(), 1.into());
+    engine
+}
+
+#[test]
+fn violation_names_the_first_violating_transaction() {
+    let mut engine = corrupted(InvariantCheck::EachTransaction);
+    let data = "type,client,tx,amount\ndeposit,2,2,1\ndeposit,2,3,1\n";
+    let error = engine
+        .load_from_reader(Reader::from_reader(data.as_bytes()))
+        .unwrap_err();
+    let Error::InvariantViolation { tx, violation } = error else {
+        panic!("expected an invariant violation, got {error:?}");
+    };
+    assert_eq!(tx, Some(2.into()));
+    assert_eq!(
+        violation,
+        Violation::TotalMismatch(1.into(), Asset::default())
+    );
+}
+
+#[test]
+fn end_of_run_violation_names_no_transaction() {
+    let mut engine = corrupted(InvariantCheck::EndOfRun);
+    let data = "type,client,tx,amount\ndeposit,2,2,1\n";
+    let error = engine
+        .load_from_reader(Reader::from_reader(data.as_bytes()))
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::InvariantViolation {
+            tx: None,
+            violation: Violation::TotalMismatch(..)
+        }
+    ));
+}
+
+#[test]
+fn unchecked_runs_ignore_violations() {
+    let mut engine = corrupted(InvariantCheck::Off);
+    let data = "type,client,tx,amount\ndeposit,2,2,1\n";
+    engine
+        .load_from_reader(Reader::from_reader(data.as_bytes()))
+        .unwrap();
+}
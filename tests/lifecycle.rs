@@ -0,0 +1,138 @@
+mod common;
+
+use common::{engine, engine_at, load_admin};
+use rust_decimal::Decimal;
+use toy_engine::{engine::ClientRecord, lifecycle::StateReason, AccountState};
+
+#[test]
+fn close_pays_out_balance() {
+    let data = "\
+type,client,tx,amount,operator,reason,timestamp
+deposit,1,1,3,,,1
+close,1,2,,ops-7,customer request,2
+deposit,1,3,1,,,3
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 0.into(), false).with_state(AccountState::Closed)]
+    );
+}
+
+#[test]
+fn close_logs_each_payout() {
+    let data = "\
+type,client,tx,amount,asset,wallet,operator,reason,timestamp
+deposit,1,1,3,,,,,1
+deposit,1,2,2,EUR,,,,1
+deposit,1,3,1.5,,savings,,,1
+close,1,4,,,,ops-7,customer request,2
+";
+    let engine = load_admin(engine(), data);
+    let payouts: Vec<_> = engine
+        .admin_log()
+        .iter()
+        .map(|entry| {
+            (
+                entry.wallet().unwrap().to_string(),
+                entry.asset().unwrap().to_string(),
+                entry.amount().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        payouts,
+        vec![
+            ("main".to_string(), "".to_string(), 3.into()),
+            ("main".to_string(), "EUR".to_string(), 2.into()),
+            ("savings".to_string(), "".to_string(), Decimal::new(15, 1)),
+        ]
+    );
+}
+
+#[test]
+fn close_refused_with_held_funds() {
+    let data = "\
+type,client,tx,amount,operator,reason,timestamp
+deposit,1,1,3,,,1
+dispute,1,1,,,,2
+close,1,2,,ops-7,customer request,3
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 3.into(), false)]
+    );
+}
+
+#[test]
+fn frozen_account_cannot_close() {
+    let data = "\
+type,client,tx,amount,operator,reason,timestamp
+deposit,1,1,3,,,1
+lock,1,2,,ops-7,fraud,2
+close,1,3,,ops-7,customer request,3
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), true)]
+    );
+}
+
+#[test]
+fn restricted_account_refuses_withdrawals() {
+    let data = "\
+type,client,tx,amount,operator,reason,timestamp
+deposit,1,1,3,,,1
+restrict,1,2,,ops-7,kyc pending,2
+withdrawal,1,3,1,,,3
+deposit,1,4,1,,,4
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 4.into(), 0.into(), false).with_state(AccountState::Restricted)]
+    );
+}
+
+#[test]
+fn dormancy_after_inactivity() {
+    let data = "\
+type,client,tx,amount,operator,reason,timestamp
+deposit,1,1,3,,,100
+deposit,2,2,3,,,100
+withdrawal,1,3,1,,,200
+deposit,2,4,1,,,200
+withdrawal,2,5,1,,,201
+";
+    let engine = load_admin(engine_at(220).with_dormant_after(50), data);
+    // Client 1 went dormant before its withdrawal, client 2 was reactivated by its deposit.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false).with_state(AccountState::Dormant),
+            ClientRecord::new(2, 3.into(), 0.into(), false),
+        ]
+    );
+    let client = engine.clients().get(&2.into()).unwrap();
+    assert_eq!(client.state_reason(), Some(&StateReason::Activity));
+}
+
+#[test]
+fn dormancy_at_end_of_run() {
+    let data = "\
+type,client,tx,amount,operator,reason,timestamp
+deposit,1,1,3,,,100
+deposit,2,2,1,,,150
+";
+    // Dormancy is as of the latest record, however late the run.
+    let engine = load_admin(engine_at(1_000).with_dormant_after(50), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false).with_state(AccountState::Dormant),
+            ClientRecord::new(2, 1.into(), 0.into(), false),
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{engine, load, reader};
+use toy_engine::{
+    engine::ClientRecord, limits::LimitBreach, transaction::TransactionId, ClientTiers, Engine,
+    IdKind, LimitTable,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn refusals(engine: &Engine) -> Vec<(TransactionId, LimitBreach)> {
+    engine
+        .refusals()
+        .iter()
+        .map(|refusal| (refusal.tx().clone(), refusal.reason()))
+        .collect()
+}
+
+const LIMITS: &str = "\
+client,tier,asset,max_amount,daily_withdrawals,monthly_withdrawals,daily_transactions,monthly_transactions
+,retail,,10,5,8,,
+2,,,,,,2,
+";
+
+fn engine_with_limits(limits: &str) -> Engine {
+    let tiers = "client,tier\n1,retail\n2,retail\n";
+    let tiers = ClientTiers::load_from_reader(reader(tiers), IdKind::Numeric).unwrap();
+    let limits = LimitTable::load_from_reader(reader(limits), IdKind::Numeric).unwrap();
+    engine().with_client_tiers(tiers).with_limits(limits)
+}
+
+#[test]
+fn withdrawal_totals_per_day_and_month() {
+    let data = format!(
+        "\
+type,client,tx,amount,timestamp
+deposit,1,1,10,0
+deposit,1,2,11,0
+withdrawal,1,3,4,0
+withdrawal,1,4,2,{}
+withdrawal,1,5,3,{}
+withdrawal,1,6,3,{}
+withdrawal,1,7,1,{}
+",
+        DAY - 1,
+        DAY,
+        2 * DAY,
+        40 * DAY,
+    );
+    let engine = load(engine_with_limits(LIMITS), &data);
+    assert_eq!(
+        refusals(&engine),
+        vec![
+            (2.into(), LimitBreach::MaxAmount),
+            (4.into(), LimitBreach::DailyWithdrawals),
+            (6.into(), LimitBreach::MonthlyWithdrawals),
+        ]
+    );
+    assert_eq!(
+        engine.clients_ordered()[0],
+        ClientRecord::new(1, 2.into(), 0.into(), false)
+    );
+}
+
+#[test]
+fn client_limits_override_their_tier() {
+    let data = "\
+type,client,tx,amount
+deposit,2,1,20
+withdrawal,2,2,7
+withdrawal,2,3,1
+";
+    let engine = load(engine_with_limits(LIMITS), data);
+    // Client 2 has no amount limits, but only two transactions a day.
+    assert_eq!(
+        refusals(&engine),
+        vec![(3.into(), LimitBreach::DailyTransactions)]
+    );
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(2, 13.into(), 0.into(), false)]
+    );
+}
+
+#[test]
+fn counters_stored_with_the_client() {
+    let data = "\
+type,client,tx,amount,asset
+deposit,1,1,5,
+withdrawal,1,2,2,
+withdrawal,1,3,9,
+deposit,1,4,50,BTC
+";
+    let engine = load(engine_with_limits(LIMITS), data);
+    // Refused transactions aren't counted, and other assets aren't limited.
+    let usage = engine.clients()[&1.into()].usage(&"".into()).unwrap();
+    assert_eq!(usage.daily_withdrawn(), 2.into());
+    assert_eq!(usage.daily_transactions(), 2);
+    assert_eq!(
+        refusals(&engine),
+        vec![(3.into(), LimitBreach::DailyWithdrawals)]
+    );
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{engine, load_admin};
+use toy_engine::{engine::ClientRecord, lifecycle::StateReason, AccountState, LockPolicy};
+
+#[test]
+fn lock_after_two_chargebacks() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,1,,
+deposit,1,2,2,,
+deposit,1,3,4,,
+dispute,1,1,,,
+chargeback,1,1,,,
+deposit,1,4,8,,
+dispute,1,2,,,
+chargeback,1,2,,,
+deposit,1,5,16,,
+";
+    let policy = LockPolicy {
+        chargebacks_to_lock: 2,
+        ..Default::default()
+    };
+    let engine = load_admin(engine().with_lock_policy(policy), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 12.into(), 0.into(), true)]
+    );
+    let client = engine.clients().values().next().unwrap();
+    assert_eq!(client.chargebacks(), 2);
+    assert_eq!(client.state_reason(), Some(&StateReason::Chargebacks(2)));
+}
+
+#[test]
+fn lock_allows_deposits_blocks_disputes() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+deposit,1,2,1,,
+lock,1,3,,ops-7,investigation
+deposit,1,4,2,,
+withdrawal,1,5,1,,
+dispute,1,2,,,
+";
+    let policy = LockPolicy {
+        blocks_deposits: false,
+        blocks_disputes: true,
+        ..Default::default()
+    };
+    let engine = load_admin(engine().with_lock_policy(policy), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 6.into(), 0.into(), true)]
+    );
+}
+
+#[test]
+fn lock_freezes_pending_disputes() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+dispute,1,1,,,
+lock,1,2,,ops-7,investigation
+resolve,1,1,,,
+";
+    let policy = LockPolicy {
+        resolves_pending_disputes: false,
+        ..Default::default()
+    };
+    let engine = load_admin(engine().with_lock_policy(policy), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 3.into(), true)]
+    );
+}
+
+#[test]
+fn lock_history_recorded() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+dispute,1,1,,,
+chargeback,1,1,,,
+unlock,1,2,,ops-7,verified
+lock,1,3,,ops-8,fraud
+";
+    let engine = load_admin(engine().with_lock_policy(LockPolicy::default()), data);
+    let client = engine.clients().values().next().unwrap();
+    let history: Vec<_> = client
+        .state_history()
+        .iter()
+        .map(|event| (event.state, event.reason.clone()))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            (AccountState::Frozen, StateReason::Chargebacks(1)),
+            (
+                AccountState::Active,
+                StateReason::Operator("verified".into())
+            ),
+            (AccountState::Frozen, StateReason::Operator("fraud".into())),
+        ]
+    );
+    assert_eq!(
+        client.state_reason(),
+        Some(&StateReason::Operator("fraud".into()))
+    );
+}
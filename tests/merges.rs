@@ -0,0 +1,70 @@
+mod common;
+
+use common::{engine, load_admin};
+use toy_engine::{engine::ClientRecord, AccountState};
+
+#[test]
+fn merge_moves_balances_and_holds() {
+    let data = "\
+type,client,tx,amount,to,operator,reason
+deposit,1,1,3,,,
+deposit,1,2,2,,,
+deposit,2,3,1,,,
+dispute,1,1,,,,
+merge,1,4,,2,ops-7,duplicate account
+deposit,1,5,1,,,
+";
+    let engine = load_admin(engine(), data);
+    // The closed source refuses further deposits.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 0.into(), 0.into(), false).with_state(AccountState::Closed),
+            ClientRecord::new(2, 3.into(), 3.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn disputes_naming_the_source_apply_to_the_target() {
+    let data = "\
+type,client,tx,amount,to,operator,reason
+deposit,1,1,3,,,
+deposit,2,2,1,,,
+dispute,1,1,,,,
+merge,1,3,,2,ops-7,duplicate account
+chargeback,1,1,,,,
+dispute,2,2,,,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 0.into(), 0.into(), false).with_state(AccountState::Closed),
+            ClientRecord::new(2, 0.into(), 1.into(), true),
+        ]
+    );
+}
+
+#[test]
+fn merge_refused_into_unknown_or_closed_accounts() {
+    let data = "\
+type,client,tx,amount,to,operator,reason
+deposit,1,1,3,,,
+deposit,2,2,1,,,
+merge,1,3,,1,ops-7,duplicate account
+merge,1,4,,3,ops-7,duplicate account
+close,2,5,,,ops-7,customer request
+merge,1,6,,2,ops-7,duplicate account
+merge,1,7,,2,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false),
+            ClientRecord::new(2, 0.into(), 0.into(), false).with_state(AccountState::Closed),
+        ]
+    );
+    assert_eq!(engine.admin_log().len(), 1);
+}
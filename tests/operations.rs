@@ -1,6 +1,6 @@
 use csv::Reader;
 use rust_decimal::{prelude::FromPrimitive, Decimal};
-use toy_engine::{engine::ClientRecord, Engine};
+use toy_engine::{engine::ClientRecord, Engine, IdKind};
 
 #[test]
 fn multiple_clients_deposit() {
@@ -338,3 +338,50 @@ withdrawal,2,5,2
         ]
     );
 }
+
+#[test]
+fn numeric_identifiers() {
+    let data = "\
+type,client,tx,amount
+deposit,4294967296,1,1
+deposit,2,3,3
+deposit,02,4,4
+dispute,2,04,
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default();
+    engine.load_from_reader(reader).unwrap();
+    let clients = engine.clients_ordered();
+    assert_eq!(
+        clients,
+        vec![
+            ClientRecord::new(2, 3.into(), 4.into(), false),
+            ClientRecord::new(4294967296, 1.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn text_identifiers() {
+    let data = "\
+type,client,tx,amount
+deposit,4294967296,1,1
+deposit,alice,2,2
+deposit,2,3,3
+deposit,02,4,4
+dispute,alice,2,
+";
+    let reader = Reader::from_reader(data.as_bytes());
+    let mut engine = Engine::default().with_id_kind(IdKind::Text);
+    engine.load_from_reader(reader).unwrap();
+    let clients = engine.clients_ordered();
+    assert_eq!(
+        clients,
+        vec![
+            ClientRecord::new("02", 4.into(), 0.into(), false),
+            ClientRecord::new("2", 3.into(), 0.into(), false),
+            ClientRecord::new("4294967296", 1.into(), 0.into(), false),
+            ClientRecord::new("alice", 0.into(), 2.into(), false),
+        ]
+    );
+}
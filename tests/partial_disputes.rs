@@ -0,0 +1,99 @@
+mod common;
+
+use common::{engine, load};
+use rust_decimal::Decimal;
+use toy_engine::{
+    engine::{ClientRecord, TransactionReport},
+    transaction::DisputeState,
+    Timestamp,
+};
+
+#[test]
+fn partial_dispute_and_resolve() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,10
+dispute,1,1,4
+dispute,1,1,3
+resolve,1,1,2
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 5.into(), 5.into(), false)]
+    );
+    assert_eq!(
+        engine.transactions_ordered(),
+        vec![TransactionReport::new(
+            1,
+            1,
+            "",
+            10.into(),
+            DisputeState::Disputed,
+            Timestamp::from_secs(1)
+        )
+        .with_amounts(5.into(), 2.into(), 0.into())]
+    );
+}
+
+#[test]
+fn partial_chargeback_then_dispute_remainder() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,10
+dispute,1,1,4
+chargeback,1,1,1
+resolve,1,1,
+dispute,1,1,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 9.into(), true)]
+    );
+    assert_eq!(
+        engine.transactions_ordered(),
+        vec![TransactionReport::new(
+            1,
+            1,
+            "",
+            10.into(),
+            DisputeState::Disputed,
+            Timestamp::from_secs(1)
+        )
+        .with_amounts(9.into(), 3.into(), 1.into())]
+    );
+}
+
+#[test]
+fn dispute_exceeding_deposit_refused() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,10
+dispute,1,1,6
+dispute,1,1,5
+dispute,1,1,-1
+resolve,1,1,7
+chargeback,1,1,0
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 4.into(), 6.into(), false)]
+    );
+}
+
+#[test]
+fn partial_amounts_with_decimals() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,1.5
+dispute,1,1,0.25
+chargeback,1,1,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, Decimal::new(125, 2), 0.into(), true)]
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::{engine, load};
+use toy_engine::{
+    engine::{ClientRecord, TransactionReport},
+    transaction::DisputeState,
+    Timestamp,
+};
+
+#[test]
+fn refund_capped_at_remaining_amount() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+deposit,1,2,5
+refund,1,1,2
+refund,1,1,4
+refund,1,1,
+refund,2,2,
+";
+    let engine = load(engine(), data);
+    // The second refund exceeds what's left, the last one names another client.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 5.into(), 0.into(), false)]
+    );
+    assert_eq!(
+        engine.transactions_ordered()[0],
+        TransactionReport::new(
+            1,
+            1,
+            "",
+            5.into(),
+            DisputeState::Undisputed,
+            Timestamp::from_secs(1)
+        )
+        .with_refunded(5.into())
+    );
+}
+
+#[test]
+fn refund_excludes_disputed_amount() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+deposit,1,2,5
+dispute,1,1,3
+refund,1,1,3
+refund,1,1,
+dispute,1,1,1
+resolve,1,1,
+";
+    let engine = load(engine(), data);
+    // Only the undisputed 2 can be refunded, and refunded funds can't be disputed.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 8.into(), 0.into(), false)]
+    );
+}
+
+#[test]
+fn refund_requires_available_funds() {
+    let data = "\
+type,client,tx,amount
+deposit,1,1,5
+withdrawal,1,2,4
+refund,1,1,2
+refund,1,1,1
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 0.into(), false)]
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{engine, load_admin};
+use toy_engine::{
+    engine::{ClientRecord, TransactionReport},
+    transaction::DisputeState,
+    Timestamp,
+};
+
+#[test]
+fn reversed_deposit_can_no_longer_be_disputed() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+deposit,1,2,2,,
+reversal,1,1,,ops-7,duplicate deposit
+reversal,1,1,,ops-7,duplicate deposit
+dispute,1,1,,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 2.into(), 0.into(), false)]
+    );
+    assert_eq!(
+        engine.transactions_ordered()[0],
+        TransactionReport::new(
+            1,
+            1,
+            "",
+            3.into(),
+            DisputeState::Reversed,
+            Timestamp::from_secs(1)
+        )
+    );
+    assert_eq!(engine.admin_log().len(), 1);
+}
+
+#[test]
+fn reversed_deposit_can_no_longer_be_represented() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+deposit,1,2,2,,
+dispute,1,1,,,
+chargeback,1,1,,,
+reversal,1,1,,ops-7,duplicate deposit
+chargeback_reversal,1,1,,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 2.into(), 0.into(), true)]
+    );
+    assert_eq!(engine.admin_log().len(), 1);
+}
+
+#[test]
+fn reversed_withdrawal_gives_the_money_back() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+withdrawal,1,2,2,,
+reversal,2,2,,ops-7,wrong client
+reversal,1,2,,ops-7,failed payout
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+    assert_eq!(engine.admin_log().len(), 1);
+}
+
+#[test]
+fn disputed_deposit_not_reversed() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+dispute,1,1,,,
+reversal,1,1,,ops-7,duplicate deposit
+resolve,1,1,,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 0.into(), false)]
+    );
+    assert!(engine.admin_log().is_empty());
+}
+
+#[test]
+fn reversal_takes_back_what_is_left() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,5,,
+deposit,1,2,5,,
+refund,1,1,2,,
+reversal,1,1,,ops-7,duplicate deposit
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 5.into(), 0.into(), false)]
+    );
+}
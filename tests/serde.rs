@@ -5,7 +5,7 @@ use std::{
 
 use csv::{ReaderBuilder, Trim, Writer};
 use tempfile::tempfile;
-use toy_engine::Engine;
+use toy_engine::{Engine, IdKind};
 
 #[test]
 fn test_example_no_whitespace() {
@@ -37,6 +37,25 @@ fn test_empty_input() {
     assert_input_output("inputs/empty.csv", "outputs/empty.csv");
 }
 
+#[test]
+fn test_string_ids() {
+    assert_engine_output(
+        Engine::default().with_id_kind(IdKind::Text),
+        "inputs/string_ids.csv",
+        "outputs/string_ids.csv",
+    );
+}
+
+#[test]
+fn test_string_ids_as_numeric() {
+    let mut engine = Engine::default();
+    let reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .from_path("inputs/string_ids.csv")
+        .unwrap();
+    assert!(engine.load_from_reader(reader).is_err());
+}
+
 #[test]
 fn test_wrong_header() {
     let mut engine = Engine::default();
@@ -48,7 +67,10 @@ fn test_wrong_header() {
 }
 
 fn assert_input_output(input_path: &str, output_path: &str) {
-    let mut engine = Engine::default();
+    assert_engine_output(Engine::default(), input_path, output_path);
+}
+
+fn assert_engine_output(mut engine: Engine, input_path: &str, output_path: &str) {
     let reader = ReaderBuilder::new()
         .trim(Trim::All)
         .from_path(input_path)
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{engine, load_admin};
+use toy_engine::engine::ClientRecord;
+
+#[test]
+fn deposit_to_locked_account_suspended() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+dispute,1,1,,,
+chargeback,1,1,,,
+deposit,1,2,5,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 0.into(), 0.into(), true)]
+    );
+    let suspense = engine.suspense_ordered();
+    assert_eq!(suspense.len(), 1);
+    assert_eq!(*suspense[0].tx(), 2.into());
+    assert_eq!(*suspense[0].client(), 1.into());
+    assert_eq!(suspense[0].amount(), 5.into());
+    assert!(!suspense[0].released());
+}
+
+#[test]
+fn release_after_unlock() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+lock,1,2,,ops-7,investigation
+deposit,1,3,5,,
+release,1,3,,ops-7,too early
+unlock,1,4,,ops-7,cleared
+release,1,3,,ops-7,cleared
+release,1,3,,ops-7,twice
+dispute,1,3,,,
+";
+    let engine = load_admin(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 3.into(), 5.into(), false)]
+    );
+    assert!(engine.suspense_ordered()[0].released());
+    assert_eq!(engine.admin_log().len(), 3);
+}
+
+#[test]
+fn release_wrong_client() {
+    let data = "\
+type,client,tx,amount,operator,reason
+deposit,1,1,3,,
+deposit,2,2,1,,
+lock,1,3,,ops-7,investigation
+deposit,1,4,5,,
+release,2,4,,ops-7,wrong client
+";
+    let engine = load_admin(engine(), data);
+    assert!(!engine.suspense_ordered()[0].released());
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{engine, engine_at, load};
+use toy_engine::{
+    engine::{ClientRecord, TransactionReport},
+    transaction::DisputeState,
+    Timestamp,
+};
+
+#[test]
+fn transfer_moves_available_funds() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+transfer,1,3,2,2
+transfer,1,4,4,2
+transfer,1,5,1,3
+transfer,1,6,1,1
+";
+    let engine = load(engine(), data);
+    // Transfers beyond the available funds, to unknown clients or to oneself are refused.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false),
+            ClientRecord::new(2, 3.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn transfer_reactivates_dormant_recipient() {
+    let data = "\
+type,client,tx,amount,to,timestamp
+deposit,1,1,5,,100
+deposit,2,2,1,,100
+deposit,1,3,1,,180
+transfer,1,4,2,2,181
+withdrawal,2,5,1,,200
+";
+    let engine = load(engine_at(210).with_dormant_after(50), data);
+    // The transfer counts as activity of the recipient, which stays active for its withdrawal.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 4.into(), 0.into(), false),
+            ClientRecord::new(2, 2.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn transfer_does_not_move_recipient_time_backwards() {
+    let data = "\
+type,client,tx,amount,to,timestamp
+deposit,2,1,10,,500
+deposit,1,2,5,,100
+transfer,1,3,2,2,200
+withdrawal,2,4,1,,300
+";
+    let engine = load(engine(), data);
+    // The withdrawal is older than the recipient's deposit, so it's still ignored.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false),
+            ClientRecord::new(2, 12.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn transfer_respects_both_lock_states() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+deposit,3,3,1,
+dispute,2,2,,
+chargeback,2,2,,
+transfer,1,4,1,2
+transfer,2,5,1,1
+transfer,1,6,1,3
+";
+    let engine = load(engine(), data);
+    // Client 2 is locked: it neither sends nor receives.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 4.into(), 0.into(), false),
+            ClientRecord::new(2, 0.into(), 0.into(), true),
+            ClientRecord::new(3, 2.into(), 0.into(), false),
+        ]
+    );
+}
+
+#[test]
+fn transfer_disputed_as_a_unit() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+transfer,1,3,2,2
+dispute,2,3,,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false),
+            ClientRecord::new(2, 1.into(), 2.into(), false),
+        ]
+    );
+    assert_eq!(
+        engine.transactions_ordered()[2],
+        TransactionReport::new(
+            3,
+            2,
+            "",
+            2.into(),
+            DisputeState::Disputed,
+            Timestamp::from_secs(1)
+        )
+    );
+}
+
+#[test]
+fn transfer_chargeback_returns_funds_to_source() {
+    let data = "\
+type,client,tx,amount,to
+deposit,1,1,5,
+deposit,2,2,1,
+transfer,1,3,2,2
+dispute,2,3,,
+chargeback,2,3,,
+chargeback_reversal,2,3,,
+";
+    let engine = load(engine(), data);
+    // The chargeback can't be reversed, the money never left the engine.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![
+            ClientRecord::new(1, 5.into(), 0.into(), false),
+            ClientRecord::new(2, 1.into(), 0.into(), true),
+        ]
+    );
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{engine, load};
+use csv::Writer;
+use toy_engine::engine::ClientRecord;
+
+const DATA: &str = "\
+type,client,tx,amount,wallet,to,to_wallet
+deposit,1,1,5,,,
+deposit,1,2,3,savings,,
+withdrawal,1,3,4,savings,,
+withdrawal,1,4,1,savings,,
+transfer,1,5,2,,1,savings
+dispute,1,2,,,,
+";
+
+#[test]
+fn balances_per_wallet() {
+    let engine = load(engine(), DATA);
+    // The withdrawal exceeding the savings wallet is refused even though the client has enough.
+    assert_eq!(
+        engine.clients_ordered(),
+        vec![ClientRecord::new(1, 4.into(), 3.into(), false)]
+    );
+    assert_eq!(
+        engine.wallets_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false).with_wallet("main"),
+            ClientRecord::new(1, 1.into(), 3.into(), false).with_wallet("savings"),
+        ]
+    );
+}
+
+#[test]
+fn accounts_output_with_wallet_rows() {
+    let data = "\
+type,client,tx,amount,wallet
+deposit,1,1,5,
+deposit,1,2,3,savings
+deposit,2,3,1,
+";
+    let engine = load(engine(), data);
+    let mut output = Vec::new();
+    engine
+        .dump_accounts(Writer::from_writer(&mut output))
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let mut lines: Vec<_> = output.lines().collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        vec![
+            "1,,8,0,8,0,false,active",
+            "1,main,5,0,5,0,false,active",
+            "1,savings,3,0,3,0,false,active",
+            "2,,1,0,1,0,false,active",
+            "client,wallet,available,held,total,credit,locked,state",
+        ]
+    );
+}
+
+#[test]
+fn moves_between_own_wallets_are_not_disputable() {
+    let data = "\
+type,client,tx,amount,wallet,to,to_wallet
+deposit,1,1,5,,,
+transfer,1,2,2,,1,savings
+dispute,1,2,,savings,,
+chargeback,1,2,,savings,,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.wallets_ordered(),
+        vec![
+            ClientRecord::new(1, 3.into(), 0.into(), false).with_wallet("main"),
+            ClientRecord::new(1, 2.into(), 0.into(), false).with_wallet("savings"),
+        ]
+    );
+}
+
+#[test]
+fn wallet_chargeback_leaves_other_wallets() {
+    let data = "\
+type,client,tx,amount,wallet
+deposit,1,1,5,
+deposit,1,2,3,savings
+dispute,1,2,,
+chargeback,1,2,,
+";
+    let engine = load(engine(), data);
+    assert_eq!(
+        engine.wallets_ordered(),
+        vec![
+            ClientRecord::new(1, 5.into(), 0.into(), true).with_wallet("main"),
+            ClientRecord::new(1, 0.into(), 0.into(), true).with_wallet("savings"),
+        ]
+    );
+}